@@ -22,6 +22,7 @@ fi
 HOST_TRIPLE=$(rustc -vV | grep host | cut -d: -f2 | tr -d " ")
 TARGET_TRIPLE=$HOST_TRIPLE
 #TARGET_TRIPLE="m68k-unknown-linux-gnu"
+#TARGET_TRIPLE="i686-unknown-linux-gnu"
 
 linker=''
 RUN_WRAPPER=''
@@ -29,6 +30,11 @@ if [[ "$HOST_TRIPLE" != "$TARGET_TRIPLE" ]]; then
    if [[ "$TARGET_TRIPLE" == "m68k-unknown-linux-gnu" ]]; then
        TARGET_TRIPLE="mips-unknown-linux-gnu"
        linker='-Clinker=m68k-linux-gcc'
+       RUN_WRAPPER='qemu-m68k -L /opt/m68k-gcc/m68k-linux'
+   elif [[ "$TARGET_TRIPLE" == "i686-unknown-linux-gnu" ]]; then
+      # We are compiling for 32-bit x86. The tests run natively and rustc already passes -m32
+      # to the linker, but GCC must have been built with multilib support.
+      linker=''
    elif [[ "$TARGET_TRIPLE" == "aarch64-unknown-linux-gnu" ]]; then
       # We are cross-compiling for aarch64. Use the correct linker and run tests in qemu.
       linker='-Clinker=aarch64-linux-gnu-gcc'
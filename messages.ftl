@@ -1,3 +1,9 @@
+codegen_gcc_compilation_process_error =
+    failed to compile module `{$module}` with libgccjit: cannot {$operation} the compilation process: {$error}
+
+codegen_gcc_compilation_process_failed =
+    failed to compile module `{$module}` with libgccjit: the compilation process exited with code {$exit_code}
+
 codegen_gcc_invalid_minimum_alignment =
     invalid minimum global alignment: {$err}
 
@@ -58,11 +64,53 @@ codegen_gcc_invalid_monomorphization_unsupported_element =
 codegen_gcc_invalid_monomorphization_unsupported_operation =
     invalid monomorphization of `{$name}` intrinsic: unsupported operation on `{$in_ty}` with element `{$in_elem}`
 
+codegen_gcc_libgccjit_compile_error =
+    libgccjit failed to compile module `{$module}`: {$error}
+
+codegen_gcc_libgccjit_error =
+    libgccjit failed to compile `{$item}`: {$error}
+
+codegen_gcc_libgccjit_error_note =
+    this is a bug in the GCC codegen backend
+
+codegen_gcc_libgccjit_error_reproducer =
+    libgccjit failed to codegen the module `{$module}`
+
 codegen_gcc_lto_not_supported =
     LTO is not supported. You may get a linker error.
 
+codegen_gcc_naked_function_stack_protection =
+    {$protection} cannot be disabled in a single function by the GCC backend, so it cannot be enabled in the codegen unit of this naked function
+    .help = move the naked functions into a module of their own and compile with enough codegen units (`-Ccodegen-units`) so that they are in a separate codegen unit
+
+codegen_gcc_native_cpu_not_supported =
+    `{$option}=native` is not supported by the GCC backend: the default CPU of GCC is used instead
+
+codegen_gcc_option_missing_value =
+    the GCC backend option `{$option}` requires a value
+
+codegen_gcc_option_unexpected_value =
+    the GCC backend option `{$option}` doesn't take a value
+
+codegen_gcc_reproducer_dumped =
+    a standalone reproducer of the module was dumped to `{$path}`
+
+codegen_gcc_reproducer_failed =
+    failed to dump a reproducer of the module in `{$dir}`
+
 codegen_gcc_tied_target_features = the target features {$features} must all be either enabled or disabled together
     .help = add the missing features in a `target_feature` attribute
 
+codegen_gcc_unknown_option =
+    unknown GCC backend option `{$option}`
+    .help = use `-Cllvm-args=-gcc-option=<option>` to pass an option to GCC
+
+codegen_gcc_unsupported_feature =
+    {$feature} is not supported by the GCC backend
+    .note = while compiling `{$item}`
+
+codegen_gcc_unsupported_reloc_model =
+    the `{$reloc_model}` relocation model is not supported by the GCC backend
+
 codegen_gcc_unwinding_inline_asm =
     GCC backend does not support unwinding from inline asm
@@ -1,9 +1,10 @@
-use gccjit::{ToLValue, ToRValue, Type};
+use gccjit::{Function, LValue, ToLValue, ToRValue, Type};
 use rustc_codegen_ssa::traits::{AbiBuilderMethods, BaseTypeMethods};
 use rustc_data_structures::fx::FxHashSet;
 use rustc_middle::bug;
 use rustc_middle::ty::Ty;
-use rustc_target::abi::call::{CastTarget, FnAbi, PassMode, Reg, RegKind};
+use rustc_target::abi::{Abi, Primitive};
+use rustc_target::abi::call::{ArgAbi, CastTarget, Conv, FnAbi, PassMode, Reg, RegKind};
 
 use crate::builder::Builder;
 use crate::context::CodegenCx;
@@ -11,8 +12,15 @@ use crate::intrinsic::ArgAbiExt;
 use crate::type_of::LayoutGccExt;
 
 impl<'a, 'gcc, 'tcx> AbiBuilderMethods<'tcx> for Builder<'a, 'gcc, 'tcx> {
-    fn get_param(&mut self, index: usize) -> Self::Value {
+    fn get_param(&mut self, mut index: usize) -> Self::Value {
         let func = self.current_func();
+        if self.struct_return_functions.borrow().contains(&func) {
+            // The return place is a local variable which is returned by `ret_void`.
+            if index == 0 {
+                return self.struct_return_slot(func).get_address(None);
+            }
+            index -= 1;
+        }
         let param = func.get_param(index as i32);
         let on_stack =
             if let Some(on_stack_param_indices) = self.on_stack_function_params.borrow().get(&func) {
@@ -30,6 +38,13 @@ impl<'a, 'gcc, 'tcx> AbiBuilderMethods<'tcx> for Builder<'a, 'gcc, 'tcx> {
     }
 }
 
+impl<'a, 'gcc, 'tcx> Builder<'a, 'gcc, 'tcx> {
+    pub fn struct_return_slot(&self, func: Function<'gcc>) -> LValue<'gcc> {
+        *self.struct_return_slots.borrow_mut().entry(func)
+            .or_insert_with(|| func.new_local(None, func.get_return_type(), "structReturn"))
+    }
+}
+
 impl GccType for CastTarget {
     fn gcc_type<'gcc>(&self, cx: &CodegenCx<'gcc, '_>) -> Type<'gcc> {
         let rest_gcc_unit = self.rest.unit.gcc_type(cx);
@@ -89,7 +104,10 @@ impl GccType for Reg {
                     _ => bug!("unsupported float: {:?}", self),
                 }
             },
-            RegKind::Vector => unimplemented!(), //cx.type_vector(cx.type_i8(), self.size.bytes()),
+            RegKind::Vector => {
+                cx.report_unsupported("passing vectors in registers");
+                cx.type_ix(self.size.bits())
+            },
         }
     }
 }
@@ -98,10 +116,17 @@ pub trait FnAbiGccExt<'gcc, 'tcx> {
     // TODO(antoyo): return a function pointer type instead?
     fn gcc_type(&self, cx: &CodegenCx<'gcc, 'tcx>) -> (Type<'gcc>, Vec<Type<'gcc>>, bool, FxHashSet<usize>);
     fn ptr_to_gcc_type(&self, cx: &CodegenCx<'gcc, 'tcx>) -> Type<'gcc>;
+    fn returns_struct_popping_pointer(&self, cx: &CodegenCx<'gcc, 'tcx>) -> bool;
 }
 
 impl<'gcc, 'tcx> FnAbiGccExt<'gcc, 'tcx> for FnAbi<'tcx, Ty<'tcx>> {
     fn gcc_type(&self, cx: &CodegenCx<'gcc, 'tcx>) -> (Type<'gcc>, Vec<Type<'gcc>>, bool, FxHashSet<usize>) {
+        // NOTE: GCC cannot pass the floats in general registers like LLVM does with soft-float,
+        // since the C ABI of x86_64 is not changed by -msoft-float.
+        if cx.sse_registers_disabled && (passes_floats_in_registers(&self.ret) || self.args.iter().any(passes_floats_in_registers)) {
+            cx.report_unsupported("passing floats without the SSE registers");
+        }
+
         let mut on_stack_param_indices = FxHashSet::default();
 
         // This capacity calculation is approximate.
@@ -114,6 +139,7 @@ impl<'gcc, 'tcx> FnAbiGccExt<'gcc, 'tcx> for FnAbi<'tcx, Ty<'tcx>> {
                 PassMode::Ignore => cx.type_void(),
                 PassMode::Direct(_) | PassMode::Pair(..) => self.ret.layout.immediate_gcc_type(cx),
                 PassMode::Cast(ref cast, _) => cast.gcc_type(cx),
+                PassMode::Indirect { .. } if self.returns_struct_popping_pointer(cx) => self.ret.memory_ty(cx),
                 PassMode::Indirect { .. } => {
                     argument_tys.push(cx.type_ptr_to(self.ret.memory_ty(cx)));
                     cx.type_void()
@@ -130,7 +156,8 @@ impl<'gcc, 'tcx> FnAbiGccExt<'gcc, 'tcx> for FnAbi<'tcx, Ty<'tcx>> {
                     continue;
                 }
                 PassMode::Indirect { extra_attrs: Some(_), .. } => {
-                    unimplemented!();
+                    cx.report_unsupported("passing unsized arguments");
+                    continue;
                 }
                 PassMode::Cast(ref cast, pad_i32) => {
                     // add padding
@@ -157,4 +184,36 @@ impl<'gcc, 'tcx> FnAbiGccExt<'gcc, 'tcx> for FnAbi<'tcx, Ty<'tcx>> {
         cx.on_stack_params.borrow_mut().insert(pointer_type.dyncast_function_ptr_type().expect("function ptr type"), on_stack_param_indices);
         pointer_type
     }
+
+    /// Whether the struct returned in memory must be returned by value in GCC.
+    ///
+    /// On x86, the callee pops the hidden pointer to the returned struct. Since GCC only does that
+    /// for the functions actually returning a struct, those are declared this way instead of
+    /// taking the pointer as their first parameter: `call` then stores the result at the address
+    /// given by rustc and `get_param` gives the address of a local returned by `ret_void`.
+    /// NOTE: this is not needed for the Rust ABI since the functions using it are always compiled
+    /// by this backend.
+    fn returns_struct_popping_pointer(&self, cx: &CodegenCx<'gcc, 'tcx>) -> bool {
+        cx.tcx.sess.target.arch == "x86"
+            && matches!(self.ret.mode, PassMode::Indirect { .. })
+            && !matches!(self.conv, Conv::Rust | Conv::RustCold)
+            && self.ret.memory_ty(cx).is_struct().is_some()
+    }
+}
+
+/// Whether the argument, or return value, is passed in floating-point registers.
+fn passes_floats_in_registers(arg: &ArgAbi<'_, Ty<'_>>) -> bool {
+    let is_float = |primitive| matches!(primitive, Primitive::F32 | Primitive::F64);
+    match arg.mode {
+        PassMode::Direct(_) | PassMode::Pair(..) =>
+            match arg.layout.abi {
+                Abi::Scalar(scalar) => is_float(scalar.primitive()),
+                Abi::ScalarPair(first, second) => is_float(first.primitive()) || is_float(second.primitive()),
+                _ => false,
+            },
+        PassMode::Cast(ref cast, _) =>
+            cast.prefix.iter().flatten().chain(std::iter::once(&cast.rest.unit))
+                .any(|reg| reg.kind == RegKind::Float),
+        PassMode::Ignore | PassMode::Indirect { .. } => false,
+    }
 }
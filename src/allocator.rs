@@ -13,6 +13,7 @@ use crate::GccContext;
 
 pub(crate) unsafe fn codegen(tcx: TyCtxt<'_>, mods: &mut GccContext, _module_name: &str, kind: AllocatorKind, alloc_error_handler_kind: AllocatorKind) {
     let context = &mods.context;
+    crate::base::add_session_options(context, tcx.sess, crate::base::NakedFunctions::None);
     let usize =
         match tcx.sess.target.pointer_width {
             16 => context.new_type::<u16>(),
@@ -94,9 +95,6 @@ fn create_wrapper_function(
         #[cfg(feature="master")]
         func.add_attribute(FnAttribute::Visibility(gccjit::Visibility::Hidden));
     }
-    if tcx.sess.must_emit_unwind_tables() {
-        // TODO(antoyo): emit unwind tables.
-    }
 
     let args: Vec<_> = types.iter().enumerate()
         .map(|(index, typ)| context.new_parameter(None, *typ, &format!("param{}", index)))
@@ -68,6 +68,29 @@ use crate::callee::get_fn;
 const ATT_SYNTAX_INS: &str = ".att_syntax noprefix\n\t";
 const INTEL_SYNTAX_INS: &str = "\n\t.intel_syntax noprefix";
 
+// When `options(nostack)` is not specified, the asm block is allowed to push to the stack and
+// to call functions, so we must give it the same guarantees LLVM gives with `alignstack`:
+// the red zone must not be clobbered and the stack must be aligned to 16 bytes.
+//
+// GCC has no equivalent to `alignstack`, so we do it by hand around the template:
+// we first skip the red zone, then push copies of the stack pointer until they fill 16 bytes before
+// aligning it. Since the `and` moves the stack pointer down by less than 16 bytes, the slot at
+// offset `16 - pointer size` from the new stack pointer always contains the unaligned value, which
+// is restored after the template.
+// This is sound because Rust asm operands are never in memory and the template must restore
+// the stack pointer to its original value.
+const X86_64_STACK_ALIGN_PROLOGUE: &str =
+    "lea rsp, [rsp - 128]\n\tpush rsp\n\tpush qword ptr [rsp]\n\tand rsp, -16\n\t";
+const X86_64_STACK_ALIGN_EPILOGUE: &str = "\n\tmov rsp, [rsp + 8]\n\tlea rsp, [rsp + 128]";
+// Used on targets that disable the red zone (e.g. kernels).
+const X86_64_NO_RED_ZONE_STACK_ALIGN_PROLOGUE: &str = "push rsp\n\tpush qword ptr [rsp]\n\tand rsp, -16\n\t";
+const X86_64_NO_RED_ZONE_STACK_ALIGN_EPILOGUE: &str = "\n\tmov rsp, [rsp + 8]";
+// There's no red zone on x86 and the stack pointer is only guaranteed to be 4-aligned, so we need
+// four copies.
+const X86_STACK_ALIGN_PROLOGUE: &str =
+    "push esp\n\tpush dword ptr [esp]\n\tpush dword ptr [esp]\n\tpush dword ptr [esp]\n\tand esp, -16\n\t";
+const X86_STACK_ALIGN_EPILOGUE: &str = "\n\tmov esp, [esp + 12]";
+
 
 struct AsmOutOperand<'a, 'tcx, 'gcc> {
     rust_idx: usize,
@@ -107,8 +130,8 @@ enum ConstraintOrRegister {
 
 
 impl<'a, 'gcc, 'tcx> AsmBuilderMethods<'tcx> for Builder<'a, 'gcc, 'tcx> {
-    fn codegen_inline_asm(&mut self, template: &[InlineAsmTemplatePiece], rust_operands: &[InlineAsmOperandRef<'tcx, Self>], options: InlineAsmOptions, span: &[Span], _instance: Instance<'_>, _dest_catch_funclet: Option<(Self::BasicBlock, Self::BasicBlock, Option<&Self::Funclet>)>) {
-        if options.contains(InlineAsmOptions::MAY_UNWIND) {
+    fn codegen_inline_asm(&mut self, template: &[InlineAsmTemplatePiece], rust_operands: &[InlineAsmOperandRef<'tcx, Self>], options: InlineAsmOptions, span: &[Span], _instance: Instance<'_>, dest_catch_funclet: Option<(Self::BasicBlock, Self::BasicBlock, Option<&Self::Funclet>)>) {
+        if cfg!(not(feature="master")) && options.contains(InlineAsmOptions::MAY_UNWIND) {
             self.sess()
                 .create_err(UnwindingInlineAsm { span: span[0] })
                 .emit();
@@ -117,7 +140,40 @@ impl<'a, 'gcc, 'tcx> AsmBuilderMethods<'tcx> for Builder<'a, 'gcc, 'tcx> {
 
         let asm_arch = self.tcx.sess.asm_arch.unwrap();
         let is_x86 = matches!(asm_arch, InlineAsmArch::X86 | InlineAsmArch::X86_64);
+
+        // NOTE: the explicit registers are given to GCC with their names in Rust, which are only
+        // known to be the same as the ones of GCC on x86.
+        if !is_x86 {
+            let explicit_register = rust_operands.iter().find_map(|op| match *op {
+                InlineAsmOperandRef::In { reg: InlineAsmRegOrRegClass::Reg(reg), .. }
+                | InlineAsmOperandRef::Out { reg: InlineAsmRegOrRegClass::Reg(reg), .. }
+                | InlineAsmOperandRef::InOut { reg: InlineAsmRegOrRegClass::Reg(reg), .. } => Some(reg),
+                _ => None,
+            });
+            if let Some(reg) = explicit_register {
+                self.cx.report_unsupported(&format!("explicit register `{}` in inline asm on this architecture", reg.name()));
+                return;
+            }
+        }
+
+        // When the asm block can unwind, it is emitted in a try block, similarly to what is done
+        // in `invoke`, so that the exception reaches the landing pad.
+        let current_block = self.block;
+        if dest_catch_funclet.is_some() {
+            // NOTE: GCC only considers that an asm block can throw with -fnon-call-exceptions.
+            if !self.cx.non_call_exceptions.replace(true) {
+                self.context.add_command_line_option("-fnon-call-exceptions");
+            }
+            self.block = self.current_func().new_block("asm_try");
+        }
+
         let att_dialect = is_x86 && options.contains(InlineAsmOptions::ATT_SYNTAX);
+        let stack_alignment = if options.contains(InlineAsmOptions::NOSTACK) {
+            None
+        }
+        else {
+            stack_align_template(self.cx, asm_arch)
+        };
 
         // GCC index of an output operand equals its position in the array
         let mut outputs = vec![];
@@ -373,7 +429,12 @@ impl<'a, 'gcc, 'tcx> AsmBuilderMethods<'tcx> for Builder<'a, 'gcc, 'tcx> {
 
         // 3. Build the template string
 
-        let mut template_str = String::with_capacity(estimate_template_length(template, constants_len, att_dialect));
+        let mut template_str = String::with_capacity(estimate_template_length(template, constants_len, att_dialect, stack_alignment));
+        // NOTE: the stack alignment code is written in Intel syntax, so it must be outside of the
+        // AT&T syntax section.
+        if let Some((prologue, _)) = stack_alignment {
+            template_str.push_str(prologue);
+        }
         if att_dialect {
             template_str.push_str(ATT_SYNTAX_INS);
         }
@@ -466,6 +527,9 @@ impl<'a, 'gcc, 'tcx> AsmBuilderMethods<'tcx> for Builder<'a, 'gcc, 'tcx> {
         if att_dialect {
             template_str.push_str(INTEL_SYNTAX_INS);
         }
+        if let Some((_, epilogue)) = stack_alignment {
+            template_str.push_str(epilogue);
+        }
 
         // 4. Generate Extended Asm block
 
@@ -480,11 +544,26 @@ impl<'a, 'gcc, 'tcx> AsmBuilderMethods<'tcx> for Builder<'a, 'gcc, 'tcx> {
             extended_asm.add_input_operand(None, &op.constraint, op.val);
         }
 
+        if stack_alignment.is_some() {
+            // NOTE: the stack alignment code moves the stack pointer without any CFI directive, so
+            // the unwinder would compute a wrong CFA while inside the template (e.g. when a
+            // function called by a `may_unwind` asm block panics) if the CFA was based on the
+            // stack pointer, which is the case when the frame pointer is omitted.
+            // Taking the frame address prevents GCC from eliminating the frame pointer in this
+            // function, so that the CFA is computed from it instead.
+            // This operand is last so that it doesn't change the index of the other operands and
+            // it's not referenced in the template.
+            let frame_address = self.context.get_builtin_function("__builtin_frame_address");
+            let frame_address = self.context.new_call(None, frame_address, &[self.context.new_rvalue_zero(self.uint_type)]);
+            extended_asm.add_input_operand(None, "X", frame_address);
+        }
+
         for clobber in clobbers.iter() {
             extended_asm.add_clobber(clobber);
         }
 
-        if !options.contains(InlineAsmOptions::PRESERVES_FLAGS) {
+        // NOTE: the `and` used to align the stack modifies the flags.
+        if !options.contains(InlineAsmOptions::PRESERVES_FLAGS) || stack_alignment.is_some() {
             // TODO(@Commeownist): I'm not 100% sure this one clobber is sufficient
             // on all architectures. For instance, what about FP stack?
             extended_asm.add_clobber("cc");
@@ -492,12 +571,11 @@ impl<'a, 'gcc, 'tcx> AsmBuilderMethods<'tcx> for Builder<'a, 'gcc, 'tcx> {
         if !options.contains(InlineAsmOptions::NOMEM) {
             extended_asm.add_clobber("memory");
         }
-        if !options.contains(InlineAsmOptions::PURE) {
+        // NOTE: GCC only considers that volatile asm blocks can throw, so the asm blocks that
+        // can unwind must be volatile even if they are pure.
+        if !options.contains(InlineAsmOptions::PURE) || options.contains(InlineAsmOptions::MAY_UNWIND) {
             extended_asm.set_volatile_flag(true);
         }
-        if !options.contains(InlineAsmOptions::NOSTACK) {
-            // TODO(@Commeownist): figure out how to align stack
-        }
         if options.contains(InlineAsmOptions::NORETURN) {
             let builtin_unreachable = self.context.get_builtin_function("__builtin_unreachable");
             let builtin_unreachable: RValue<'gcc> = unsafe { std::mem::transmute(builtin_unreachable) };
@@ -518,10 +596,33 @@ impl<'a, 'gcc, 'tcx> AsmBuilderMethods<'tcx> for Builder<'a, 'gcc, 'tcx> {
                 OperandValue::Immediate(op.tmp_var.to_rvalue()).store(self, place);
             }
         }
+
+        if let Some((dest, catch, _funclet)) = dest_catch_funclet {
+            let try_block = self.block;
+            self.block = current_block;
+            #[cfg(feature="master")]
+            self.end_with_try(try_block, dest, catch);
+            #[cfg(not(feature="master"))]
+            let _ = (try_block, dest, catch);
+        }
     }
 }
 
-fn estimate_template_length(template: &[InlineAsmTemplatePiece], constants_len: usize, att_dialect: bool) -> usize {
+/// Returns the prologue and epilogue that need to surround the template so that the asm block can
+/// use the stack, or `None` if the target already guarantees it.
+fn stack_align_template(cx: &CodegenCx<'_, '_>, asm_arch: InlineAsmArch) -> Option<(&'static str, &'static str)> {
+    match asm_arch {
+        InlineAsmArch::X86_64 if cx.sess().target.disable_redzone =>
+            Some((X86_64_NO_RED_ZONE_STACK_ALIGN_PROLOGUE, X86_64_NO_RED_ZONE_STACK_ALIGN_EPILOGUE)),
+        InlineAsmArch::X86_64 => Some((X86_64_STACK_ALIGN_PROLOGUE, X86_64_STACK_ALIGN_EPILOGUE)),
+        InlineAsmArch::X86 => Some((X86_STACK_ALIGN_PROLOGUE, X86_STACK_ALIGN_EPILOGUE)),
+        // NOTE: the other architectures either don't have a red zone or require the stack
+        // pointer to always be aligned.
+        _ => None,
+    }
+}
+
+fn estimate_template_length(template: &[InlineAsmTemplatePiece], constants_len: usize, att_dialect: bool, stack_alignment: Option<(&str, &str)>) -> usize {
     let len: usize = template.iter().map(|piece| {
         match *piece {
             InlineAsmTemplatePiece::String(ref string) => {
@@ -543,6 +644,9 @@ fn estimate_template_length(template: &[InlineAsmTemplatePiece], constants_len:
     if att_dialect {
         res += INTEL_SYNTAX_INS.len() + ATT_SYNTAX_INS.len();
     }
+    if let Some((prologue, epilogue)) = stack_alignment {
+        res += prologue.len() + epilogue.len();
+    }
     res
 }
 
@@ -564,7 +668,7 @@ fn reg_to_gcc(reg: InlineAsmRegOrRegClass) -> ConstraintOrRegister {
                     });
                 }
 
-                _ => unimplemented!(),
+                _ => unreachable!("explicit registers are reported as unsupported on this architecture"),
             }
         },
         // They can be retrieved from https://gcc.gnu.org/onlinedocs/gcc/Machine-Constraints.html
@@ -649,10 +753,10 @@ fn reg_to_gcc(reg: InlineAsmRegOrRegClass) -> ConstraintOrRegister {
 fn dummy_output_type<'gcc, 'tcx>(cx: &CodegenCx<'gcc, 'tcx>, reg: InlineAsmRegClass) -> Type<'gcc> {
     match reg {
         InlineAsmRegClass::AArch64(AArch64InlineAsmRegClass::reg) => cx.type_i32(),
-        InlineAsmRegClass::AArch64(AArch64InlineAsmRegClass::preg) => unimplemented!(),
+        InlineAsmRegClass::AArch64(AArch64InlineAsmRegClass::preg) => unreachable!("clobber-only"),
         InlineAsmRegClass::AArch64(AArch64InlineAsmRegClass::vreg)
         | InlineAsmRegClass::AArch64(AArch64InlineAsmRegClass::vreg_low16) => {
-            unimplemented!()
+            unsupported_output_type(cx, reg)
         }
         InlineAsmRegClass::Arm(ArmInlineAsmRegClass::reg)=> cx.type_i32(),
         InlineAsmRegClass::Arm(ArmInlineAsmRegClass::sreg)
@@ -663,10 +767,10 @@ fn dummy_output_type<'gcc, 'tcx>(cx: &CodegenCx<'gcc, 'tcx>, reg: InlineAsmRegCl
         InlineAsmRegClass::Arm(ArmInlineAsmRegClass::qreg)
         | InlineAsmRegClass::Arm(ArmInlineAsmRegClass::qreg_low8)
         | InlineAsmRegClass::Arm(ArmInlineAsmRegClass::qreg_low4) => {
-            unimplemented!()
+            unsupported_output_type(cx, reg)
         }
-        InlineAsmRegClass::Avr(_) => unimplemented!(),
-        InlineAsmRegClass::Bpf(_) => unimplemented!(),
+        InlineAsmRegClass::Avr(_) => unsupported_output_type(cx, reg),
+        InlineAsmRegClass::Bpf(_) => unsupported_output_type(cx, reg),
         InlineAsmRegClass::Hexagon(HexagonInlineAsmRegClass::reg) => cx.type_i32(),
         InlineAsmRegClass::LoongArch(LoongArchInlineAsmRegClass::reg) => cx.type_i32(),
         InlineAsmRegClass::LoongArch(LoongArchInlineAsmRegClass::freg) => cx.type_f32(),
@@ -675,7 +779,7 @@ fn dummy_output_type<'gcc, 'tcx>(cx: &CodegenCx<'gcc, 'tcx>, reg: InlineAsmRegCl
         InlineAsmRegClass::M68k(M68kInlineAsmRegClass::reg_data) => cx.type_i32(),
         InlineAsmRegClass::Mips(MipsInlineAsmRegClass::reg) => cx.type_i32(),
         InlineAsmRegClass::Mips(MipsInlineAsmRegClass::freg) => cx.type_f32(),
-        InlineAsmRegClass::Msp430(_) => unimplemented!(),
+        InlineAsmRegClass::Msp430(_) => unsupported_output_type(cx, reg),
         InlineAsmRegClass::Nvptx(NvptxInlineAsmRegClass::reg16) => cx.type_i16(),
         InlineAsmRegClass::Nvptx(NvptxInlineAsmRegClass::reg32) => cx.type_i32(),
         InlineAsmRegClass::Nvptx(NvptxInlineAsmRegClass::reg64) => cx.type_i64(),
@@ -692,14 +796,14 @@ fn dummy_output_type<'gcc, 'tcx>(cx: &CodegenCx<'gcc, 'tcx>, reg: InlineAsmRegCl
         InlineAsmRegClass::X86(X86InlineAsmRegClass::reg)
         | InlineAsmRegClass::X86(X86InlineAsmRegClass::reg_abcd) => cx.type_i32(),
         InlineAsmRegClass::X86(X86InlineAsmRegClass::reg_byte) => cx.type_i8(),
-        InlineAsmRegClass::X86(X86InlineAsmRegClass::mmx_reg) => unimplemented!(),
+        InlineAsmRegClass::X86(X86InlineAsmRegClass::mmx_reg) => unsupported_output_type(cx, reg),
         InlineAsmRegClass::X86(X86InlineAsmRegClass::xmm_reg)
         | InlineAsmRegClass::X86(X86InlineAsmRegClass::ymm_reg)
         | InlineAsmRegClass::X86(X86InlineAsmRegClass::zmm_reg) => cx.type_f32(),
-        InlineAsmRegClass::X86(X86InlineAsmRegClass::x87_reg) => unimplemented!(),
+        InlineAsmRegClass::X86(X86InlineAsmRegClass::x87_reg) => unsupported_output_type(cx, reg),
         InlineAsmRegClass::X86(X86InlineAsmRegClass::kreg) => cx.type_i16(),
         InlineAsmRegClass::X86(X86InlineAsmRegClass::kreg0) => cx.type_i16(),
-        InlineAsmRegClass::X86(X86InlineAsmRegClass::tmm_reg) => unimplemented!(),
+        InlineAsmRegClass::X86(X86InlineAsmRegClass::tmm_reg) => unsupported_output_type(cx, reg),
         InlineAsmRegClass::Wasm(WasmInlineAsmRegClass::local) => cx.type_i32(),
         InlineAsmRegClass::SpirV(SpirVInlineAsmRegClass::reg) => {
             bug!("LLVM backend does not support SPIR-V")
@@ -710,6 +814,11 @@ fn dummy_output_type<'gcc, 'tcx>(cx: &CodegenCx<'gcc, 'tcx>, reg: InlineAsmRegCl
     }
 }
 
+fn unsupported_output_type<'gcc, 'tcx>(cx: &CodegenCx<'gcc, 'tcx>, reg: InlineAsmRegClass) -> Type<'gcc> {
+    cx.report_unsupported(&format!("discarded outputs in register class `{}`", reg.name()));
+    cx.type_i32()
+}
+
 impl<'gcc, 'tcx> AsmMethods<'tcx> for CodegenCx<'gcc, 'tcx> {
     fn codegen_global_asm(&self, template: &[InlineAsmTemplatePiece], operands: &[GlobalAsmOperandRef<'tcx>], options: InlineAsmOptions, _line_spans: &[Span]) {
         let asm_arch = self.tcx.sess.asm_arch.unwrap();
@@ -1,19 +1,19 @@
+use std::borrow::Cow;
+
 #[cfg(feature="master")]
 use gccjit::FnAttribute;
 use gccjit::Function;
-use rustc_attr::InstructionSetAttr;
-#[cfg(feature="master")]
-use rustc_attr::InlineAttr;
+use rustc_attr::{InlineAttr, InstructionSetAttr};
 use rustc_codegen_ssa::target_features::tied_target_features;
 use rustc_data_structures::fx::FxHashMap;
-use rustc_middle::ty;
-#[cfg(feature="master")]
+use rustc_middle::ty::{self, TyCtxt};
 use rustc_middle::middle::codegen_fn_attrs::CodegenFnAttrFlags;
 use rustc_session::Session;
+use rustc_span::def_id::DefId;
 use rustc_span::symbol::sym;
 use smallvec::{smallvec, SmallVec};
 
-use crate::{context::CodegenCx, errors::TiedTargetFeatures};
+use crate::{context::CodegenCx, errors::TiedTargetFeatures, tune_cpu};
 
 // Given a map from target_features to whether they are enabled or disabled,
 // ensure only valid combinations are allowed.
@@ -71,6 +71,67 @@ fn to_gcc_features<'a>(sess: &Session, s: &'a str) -> SmallVec<[&'a str; 2]> {
     }
 }
 
+/// Get the GCC options selecting the CPU given with `-Ctarget-cpu` and `-Ztune-cpu`.
+///
+/// NOTE: the CPU is selected for the whole module rather than with the target attribute of every
+/// function because arch= in this attribute resets the ISA flags to the baseline of the CPU, which
+/// would disable the features enabled for the module. The default CPU of the target is not
+/// selected since it's also the default of GCC.
+pub fn target_cpu_options(sess: &Session) -> SmallVec<[String; 2]> {
+    let arch = &*sess.target.arch;
+    // NOTE: the CPU is selected with -march on these architectures, but with -mcpu on the others.
+    let cpu_option =
+        match arch {
+            "x86" | "x86_64" | "s390x" | "mips" | "mips64" | "loongarch64" => "-march",
+            _ => "-mcpu",
+        };
+
+    let mut options = SmallVec::new();
+    if let Some(cpu) = sess.opts.cg.target_cpu.as_deref().and_then(|cpu| gcc_cpu_name(arch, cpu)) {
+        options.push(format!("{}={}", cpu_option, cpu));
+    }
+    if let Some(cpu) = tune_cpu(sess).and_then(|cpu| gcc_cpu_name(arch, cpu)) {
+        options.push(format!("-mtune={}", cpu));
+    }
+    options
+}
+
+/// Converts the LLVM name of a CPU, as used by rustc, to its GCC name. Returns `None` when GCC
+/// should use its default CPU.
+fn gcc_cpu_name<'a>(arch: &str, cpu: &'a str) -> Option<Cow<'a, str>> {
+    let name =
+        match (arch, cpu) {
+            // NOTE: generic (or generic-rv64 on RISC-V) is not a CPU name known by GCC: it means its
+            // default CPU. native is reported as unsupported in `init` since it's only handled by
+            // the GCC driver.
+            (_, "native") => return None,
+            (_, _) if cpu.starts_with("generic") => return None,
+            ("powerpc" | "powerpc64", "ppc") => "powerpc",
+            ("powerpc" | "powerpc64", "ppc64") => "powerpc64",
+            ("powerpc" | "powerpc64", "ppc64le") => "powerpc64le",
+            ("powerpc" | "powerpc64", "g3") => "750",
+            ("powerpc" | "powerpc64", "g4") => "7400",
+            ("powerpc" | "powerpc64", "g4+") => "7450",
+            ("powerpc" | "powerpc64", "g5") => "970",
+            ("powerpc" | "powerpc64", "pwr5x") => "power5+",
+            ("powerpc" | "powerpc64", _) if cpu.starts_with("pwr") => {
+                return Some(Cow::Owned(format!("power{}", &cpu["pwr".len()..])));
+            },
+            _ => cpu,
+        };
+    Some(Cow::Borrowed(name))
+}
+
+/// Whether the item is in one of the paths, which start with the name of the crate.
+fn is_in_paths(tcx: TyCtxt<'_>, def_id: DefId, paths: &[String]) -> bool {
+    let def_path = tcx.def_path(def_id);
+    let path = std::iter::once(tcx.crate_name(def_id.krate).to_string())
+        .chain(def_path.data.iter().map(|data| data.data.to_string()))
+        .collect::<Vec<_>>()
+        .join("::");
+    paths.iter().any(|excluded| path == *excluded || path.starts_with(&format!("{}::", excluded)))
+}
+
 /// Get GCC attribute for the provided inline heuristic.
 #[cfg(feature="master")]
 #[inline]
@@ -99,6 +160,15 @@ pub fn from_fn_attrs<'gcc, 'tcx>(
 ) {
     let codegen_fn_attrs = cx.tcx.codegen_fn_attrs(instance.def_id());
 
+    if let Some(ref excluded_paths) = cx.instrument_functions_exclude {
+        if codegen_fn_attrs.inline == InlineAttr::Always
+            || codegen_fn_attrs.flags.contains(CodegenFnAttrFlags::NAKED)
+            || is_in_paths(cx.tcx, instance.def_id(), excluded_paths)
+        {
+            cx.no_instrument_functions.borrow_mut().push(cx.tcx.symbol_name(instance).name.to_string());
+        }
+    }
+
     #[cfg(feature="master")]
     {
         let inline =
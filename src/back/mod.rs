@@ -1 +1,3 @@
+pub mod reproducer;
+pub mod timings;
 pub mod write;
@@ -0,0 +1,99 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use gccjit::Context;
+#[cfg(unix)]
+use gccjit::OutputKind;
+
+#[cfg(unix)]
+use crate::back::write::redirect_stderr;
+use crate::errors::Reproducer;
+
+/// Writes a standalone C reproducer of the module in `dir` and, if `with_gimple` is true, the
+/// GIMPLE generated for it next to it.
+///
+/// Returns the path of the reproducer if it could be written.
+pub fn dump_reproducer(context: &Context<'_>, dir: &Path, module_name: &str, with_gimple: bool) -> Option<PathBuf> {
+    fs::create_dir_all(dir).ok()?;
+    let reproducer_path = dir.join(format!("{}.c", module_name));
+    let reproducer = reproducer_path.to_str()?;
+
+    // FIXME(antoyo): segfault in dump_reproducer_to_file() might be caused by
+    // transmuting an rvalue to an lvalue.
+    // Segfault is actually in gcc::jit::reproducer::get_identifier_as_lvalue
+    // NOTE: this is why the reproducer is dumped in a child process: a crash there must not hide
+    // the error that is being reported.
+    if !run_in_child(|| context.dump_reproducer_to_file(reproducer)) {
+        let _ = fs::remove_file(&reproducer_path);
+        return None;
+    }
+
+    // NOTE: libgccjit prints the initial GIMPLE to stderr while compiling, so it can only be
+    // captured by compiling the module again in a forked process.
+    #[cfg(unix)]
+    if with_gimple {
+        let gimple_path = dir.join(format!("{}.gimple", module_name));
+        let asm_path = dir.join(format!("{}.s", module_name));
+        if let Some(asm) = asm_path.to_str() {
+            // NOTE: only the GIMPLE is kept: it is printed before the optimization passes run, so
+            // it is written even if the compilation fails.
+            run_in_child(|| unsafe {
+                redirect_stderr(&gimple_path);
+                context.set_dump_initial_gimple(true);
+                context.compile_to_file(OutputKind::Assembler, asm);
+            });
+            let _ = fs::remove_file(&asm_path);
+        }
+    }
+    #[cfg(not(unix))]
+    let _ = with_gimple;
+
+    Some(reproducer_path)
+}
+
+/// Result of dumping the reproducer of a module that failed to compile.
+pub enum ReproducerDump {
+    Dumped(String),
+    Failed(String),
+}
+
+impl ReproducerDump {
+    pub fn dump(context: &Context<'_>, dir: &Path, module_name: &str, with_gimple: bool) -> Self {
+        match dump_reproducer(context, dir, module_name, with_gimple) {
+            Some(path) => ReproducerDump::Dumped(path.display().to_string()),
+            None => ReproducerDump::Failed(dir.display().to_string()),
+        }
+    }
+
+    pub fn as_note(&self) -> Reproducer<'_> {
+        match *self {
+            ReproducerDump::Dumped(ref path) => Reproducer::Dumped { path },
+            ReproducerDump::Failed(ref dir) => Reproducer::Failed { dir },
+        }
+    }
+}
+
+/// Runs `func` in a forked process and returns whether it exited normally.
+#[cfg(unix)]
+fn run_in_child<F: FnOnce()>(func: F) -> bool {
+    unsafe {
+        match libc::fork() {
+            -1 => false,
+            0 => {
+                func();
+                // NOTE: don't run the destructors and atexit handlers of the parent process.
+                libc::_exit(0);
+            },
+            pid => {
+                let mut status = 0;
+                libc::waitpid(pid, &mut status, 0) != -1 && libc::WIFEXITED(status) && libc::WEXITSTATUS(status) == 0
+            },
+        }
+    }
+}
+
+#[cfg(not(unix))]
+fn run_in_child<F: FnOnce()>(func: F) -> bool {
+    func();
+    true
+}
@@ -0,0 +1,131 @@
+use std::fs;
+use std::path::Path;
+use std::sync::Mutex;
+
+use rustc_codegen_ssa::back::write::CodegenContext;
+use rustc_data_structures::profiling::SelfProfilerRef;
+use rustc_session::config::Options;
+
+use crate::GccCodegenBackend;
+
+/// Reports generated by GCC's `-ftime-report` for every compiled module.
+///
+/// NOTE: this needs to be global because `WriteBackendMethods::codegen` doesn't have access to
+/// the backend. LLVM also keeps its pass timings in a global state.
+static TIME_REPORTS: Mutex<Vec<(String, String)>> = Mutex::new(Vec::new());
+
+/// Mapping from GCC's phases to the name of the events recorded by the self-profiler.
+const PHASE_EVENTS: &[(&str, &str)] = &[
+    ("phase setup", "GCC_setup"),
+    ("phase parsing", "GCC_gimple_build"),
+    ("phase lang. deferred", "GCC_gimple_build"),
+    ("phase last asm", "GCC_emit_asm"),
+    ("phase finalize", "GCC_finalize"),
+];
+
+/// The phase running both the GIMPLE optimizations and the RTL passes, which is split into the
+/// `GCC_optimize` and `GCC_rtl` events.
+const OPT_AND_GENERATE_PHASE: &str = "phase opt and generate";
+
+/// Prefixes of the timers of the GIMPLE and IPA passes in the report. The other passes running in
+/// the "opt and generate" phase work on RTL.
+const GIMPLE_TIMER_PREFIXES: &[&str] = &["tree ", "ipa "];
+
+/// Whether GCC should be asked to report the time spent in its passes.
+pub fn needs_time_report(opts: &Options, prof: &SelfProfilerRef) -> bool {
+    // NOTE: the report can only be captured in a forked process.
+    cfg!(unix) && (opts.unstable_opts.time_llvm_passes || prof.enabled())
+}
+
+/// Records the phases of the report that GCC wrote to `report_path` while compiling a module in
+/// the self-profile and saves the report to print it for `-Ztime-llvm-passes`.
+pub fn record_time_report(cgcx: &CodegenContext<GccCodegenBackend>, module_name: &str, report_path: &Path) {
+    let report =
+        match fs::read_to_string(report_path) {
+            Ok(report) => report,
+            Err(_) => return,
+        };
+    if !cgcx.save_temps {
+        let _ = fs::remove_file(report_path);
+    }
+
+    if cgcx.prof.enabled() {
+        record_phases(&cgcx.prof, module_name, &report);
+    }
+
+    // NOTE: the reports are only printed for -Ztime-llvm-passes, so don't keep them otherwise.
+    if cgcx.opts.unstable_opts.time_llvm_passes {
+        TIME_REPORTS.lock().expect("time reports lock").push((module_name.to_string(), report));
+    }
+}
+
+/// Records the phases of the compilation of a module as events of the self-profile of rustc.
+///
+/// NOTE: the self-profiler can only record events as they happen, but the compilation of a module
+/// is a single call to libgccjit, done in another process, and its phases are only known from the
+/// report written at the end. So the phases are recorded once the compilation is done, with the
+/// name of the module and the wall time measured by GCC as arguments.
+fn record_phases(prof: &SelfProfilerRef, module_name: &str, report: &str) {
+    for (event, wall_time) in phase_events(report) {
+        let _timer = prof.generic_activity_with_arg_recorder(event, |recorder| {
+            recorder.record_arg(module_name);
+            recorder.record_arg(format!("{:.3}s", wall_time));
+        });
+    }
+}
+
+pub fn print_time_reports() {
+    let reports = std::mem::take(&mut *TIME_REPORTS.lock().expect("time reports lock"));
+    for (module_name, report) in reports {
+        eprintln!("GCC time report for module {}:", module_name);
+        eprintln!("{}", report);
+    }
+}
+
+/// Returns the events of the phases of the report with their wall time, in seconds, in the order
+/// in which they were run.
+///
+/// NOTE: GCC runs the GIMPLE optimizations and the RTL passes function by function, so they're
+/// interleaved in time. They are recorded as two consecutive events whose durations are the sum of
+/// the timers of their passes.
+fn phase_events(report: &str) -> Vec<(&'static str, f64)> {
+    let gimple_passes_time: f64 = parse_timers(report)
+        .filter(|(name, _)| GIMPLE_TIMER_PREFIXES.iter().any(|prefix| name.starts_with(prefix)))
+        .map(|(_, wall_time)| wall_time)
+        .sum();
+
+    let mut events: Vec<(&'static str, f64)> = vec![];
+    let mut add_event = |event, wall_time| {
+        match events.last_mut() {
+            Some((last_event, last_wall_time)) if *last_event == event => *last_wall_time += wall_time,
+            _ => events.push((event, wall_time)),
+        }
+    };
+    for (name, wall_time) in parse_timers(report) {
+        if name == OPT_AND_GENERATE_PHASE {
+            let optimize_time = gimple_passes_time.min(wall_time);
+            add_event("GCC_optimize", optimize_time);
+            add_event("GCC_rtl", wall_time - optimize_time);
+        }
+        else if let Some(&(_, event)) = PHASE_EVENTS.iter().find(|&&(phase, _)| phase == name) {
+            add_event(event, wall_time);
+        }
+    }
+    events
+}
+
+/// Returns the name and the wall time, in seconds, of every line of the report.
+///
+/// The lines look like this:
+///  phase setup          :   0.00 (  0%)   0.00 (  0%)   0.01 ( 11%)  1546k ( 45%)
+/// where the columns are the user time, the system time, the wall time and the memory usage.
+fn parse_timers(report: &str) -> impl Iterator<Item = (&str, f64)> {
+    report.lines()
+        .filter_map(|line| {
+            let (name, times) = line.split_once(':')?;
+            let wall_time = times.split_whitespace()
+                .filter_map(|time| time.parse::<f64>().ok())
+                .nth(2)?;
+            Some((name.trim(), wall_time))
+        })
+}
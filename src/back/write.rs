@@ -1,6 +1,12 @@
-use std::{env, fs};
+use std::fs;
+#[cfg(unix)]
+use std::ffi::CString;
+#[cfg(unix)]
+use std::io;
+#[cfg(unix)]
+use std::path::Path;
 
-use gccjit::OutputKind;
+use gccjit::{Context, OutputKind};
 use rustc_codegen_ssa::{CompiledModule, ModuleCodegen};
 use rustc_codegen_ssa::back::write::{CodegenContext, EmitObj, ModuleConfig};
 use rustc_errors::Handler;
@@ -9,11 +15,17 @@ use rustc_span::fatal_error::FatalError;
 use rustc_target::spec::SplitDebuginfo;
 
 use crate::{GccCodegenBackend, GccContext};
+use crate::back::reproducer::{ReproducerDump, dump_reproducer};
+#[cfg(unix)]
+use crate::back::timings::{needs_time_report, record_time_report};
+use crate::errors::{CompilationProcessError, CompilationProcessFailed, LibgccjitCompileError, LibgccjitErrorReproducer};
+use crate::options::GccOptions;
 
-pub(crate) unsafe fn codegen(cgcx: &CodegenContext<GccCodegenBackend>, _diag_handler: &Handler, module: ModuleCodegen<GccContext>, config: &ModuleConfig) -> Result<CompiledModule, FatalError> {
-    let _timer = cgcx.prof.generic_activity_with_arg("LLVM_module_codegen", &*module.name);
+pub(crate) unsafe fn codegen(cgcx: &CodegenContext<GccCodegenBackend>, diag_handler: &Handler, module: ModuleCodegen<GccContext>, config: &ModuleConfig) -> Result<CompiledModule, FatalError> {
+    let _timer = cgcx.prof.generic_activity_with_arg("GCC_module_codegen", &*module.name);
     {
         let context = &module.module_llvm.context;
+        let options = GccOptions::new(&cgcx.opts);
 
         let module_name = module.name.clone();
         let module_name = Some(&module_name[..]);
@@ -32,35 +44,35 @@ pub(crate) unsafe fn codegen(cgcx: &CodegenContext<GccCodegenBackend>, _diag_han
         if config.emit_asm {
             let _timer = cgcx
                 .prof
-                .generic_activity_with_arg("LLVM_module_codegen_emit_asm", &*module.name);
+                .generic_activity_with_arg("GCC_module_codegen_emit_asm", &*module.name);
             let path = cgcx.output_filenames.temp_path(OutputType::Assembly, module_name);
-            context.compile_to_file(OutputKind::Assembler, path.to_str().expect("path to str"));
+            compile_to_file(cgcx, diag_handler, &module, OutputKind::Assembler, path.to_str().expect("path to str"))?;
         }
 
         match config.emit_obj {
             EmitObj::ObjectCode(_) => {
                 let _timer = cgcx
                     .prof
-                    .generic_activity_with_arg("LLVM_module_codegen_emit_obj", &*module.name);
-                if env::var("CG_GCCJIT_DUMP_MODULE_NAMES").as_deref() == Ok("1") {
+                    .generic_activity_with_arg("GCC_module_codegen_emit_obj", &*module.name);
+                if options.dump_module_names {
                     println!("Module {}", module.name);
                 }
-                if env::var("CG_GCCJIT_DUMP_ALL_MODULES").as_deref() == Ok("1") || env::var("CG_GCCJIT_DUMP_MODULE").as_deref() == Ok(&module.name) {
+                if options.dump_reproducer.matches(&module.name) {
                     println!("Dumping reproducer {}", module.name);
-                    let _ = fs::create_dir("/tmp/reproducers");
-                    // FIXME(antoyo): segfault in dump_reproducer_to_file() might be caused by
-                    // transmuting an rvalue to an lvalue.
-                    // Segfault is actually in gcc::jit::reproducer::get_identifier_as_lvalue
-                    context.dump_reproducer_to_file(&format!("/tmp/reproducers/{}.c", module.name));
-                    println!("Dumped reproducer {}", module.name);
+                    let dir = options.reproducer_dir(&cgcx.output_filenames);
+                    match dump_reproducer(context, &dir, &module.name, true) {
+                        Some(path) => println!("Dumped reproducer {}", path.display()),
+                        None => println!("Failed to dump reproducer {}", module.name),
+                    }
                 }
-                if env::var("CG_GCCJIT_DUMP_TO_FILE").as_deref() == Ok("1") {
-                    let _ = fs::create_dir("/tmp/gccjit_dumps");
-                    let path = &format!("/tmp/gccjit_dumps/{}.c", module.name);
+                if options.dump_to_file.matches(&module.name) {
+                    let dir = options.dump_dir();
+                    let _ = fs::create_dir_all(&dir);
+                    let path = dir.join(format!("{}.c", module.name));
                     context.set_debug_info(true);
-                    context.dump_to_file(path, true);
+                    context.dump_to_file(path.to_str().expect("path to str"), true);
                 }
-                context.compile_to_file(OutputKind::ObjectFile, obj_out.to_str().expect("path to str"));
+                compile_to_file(cgcx, diag_handler, &module, OutputKind::ObjectFile, obj_out.to_str().expect("path to str"))?;
             }
 
             EmitObj::Bitcode => {
@@ -79,6 +91,138 @@ pub(crate) unsafe fn codegen(cgcx: &CodegenContext<GccCodegenBackend>, _diag_han
     ))
 }
 
+/// libgccjit holds a global mutex during the compilation of a context, so compiling codegen units
+/// from multiple worker threads would be serialised.
+/// To actually run GCC in parallel, the compilation is done in a forked process which has its own
+/// copy of the libgccjit state.
+///
+/// NOTE: this is safe because the parent process never compiles a context when parallel codegen
+/// is enabled, so the libgccjit mutex cannot be held by another thread at the time of the fork.
+fn compile_to_file(cgcx: &CodegenContext<GccCodegenBackend>, diag_handler: &Handler, module: &ModuleCodegen<GccContext>, kind: OutputKind, path: &str) -> Result<(), FatalError> {
+    let context = &module.module_llvm.context;
+    let module_name = &*module.name;
+
+    // NOTE: the error was already reported while codegenning the item that caused it.
+    if module.module_llvm.error_reported {
+        if module.module_llvm.libgccjit_error {
+            // NOTE: libgccjit refuses to compile a context that has an error, so there's no GIMPLE
+            // to dump.
+            let dir = GccOptions::new(&cgcx.opts).reproducer_dir(&cgcx.output_filenames);
+            let reproducer = ReproducerDump::dump(context, &dir, module_name, false);
+            diag_handler.emit_note(LibgccjitErrorReproducer { module: module_name, reproducer: reproducer.as_note() });
+        }
+        return Err(FatalError);
+    }
+
+    // NOTE: GCC writes its time report to stderr, so it's only possible to capture it in a forked
+    // process.
+    let time_report = needs_time_report(&cgcx.opts, &cgcx.prof);
+
+    #[cfg(unix)]
+    if !cgcx.opts.unstable_opts.no_parallel_llvm || time_report {
+        let report_path = cgcx.output_filenames.temp_path_ext("gcc-time-report", Some(module_name));
+        // The child process writes the libgccjit error, if any, to this file.
+        // NOTE: a pipe is not used here because the processes forked by the other worker threads
+        // would inherit its write end, so the parent would only see the end of file once all of
+        // them exited.
+        let error_path = cgcx.output_filenames.temp_path_ext("gcc-error", Some(module_name));
+        let _ = fs::remove_file(&error_path);
+        unsafe {
+            match libc::fork() {
+                -1 => {
+                    // NOTE: don't fallback to compiling in this process since the processes forked
+                    // by the other worker threads in the meantime would inherit the locked
+                    // libgccjit mutex.
+                    report_process_error(diag_handler, module_name, "fork", io::Error::last_os_error());
+                    return Err(FatalError);
+                },
+                0 => {
+                    if time_report {
+                        redirect_stderr(&report_path);
+                    }
+                    context.compile_to_file(kind, path);
+                    let exit_code =
+                        if let Ok(Some(error)) = context.get_first_error() {
+                            let _ = fs::write(&error_path, error);
+                            1
+                        }
+                        else {
+                            0
+                        };
+                    // NOTE: don't run the destructors and atexit handlers of the parent process.
+                    libc::_exit(exit_code);
+                },
+                pid => {
+                    let mut status = 0;
+                    while libc::waitpid(pid, &mut status, 0) == -1 {
+                        let error = io::Error::last_os_error();
+                        if error.kind() != io::ErrorKind::Interrupted {
+                            report_process_error(diag_handler, module_name, "wait for", error);
+                            return Err(FatalError);
+                        }
+                    }
+                    if let Ok(error) = fs::read_to_string(&error_path) {
+                        let _ = fs::remove_file(&error_path);
+                        if !error.is_empty() {
+                            report_compile_error(cgcx, diag_handler, context, module_name, &error);
+                            return Err(FatalError);
+                        }
+                    }
+                    if !libc::WIFEXITED(status) || libc::WEXITSTATUS(status) != 0 {
+                        let exit_code = if libc::WIFEXITED(status) { Some(libc::WEXITSTATUS(status)) } else { None };
+                        report_process_failure(cgcx, diag_handler, context, module_name, exit_code);
+                        return Err(FatalError);
+                    }
+                    if time_report {
+                        record_time_report(cgcx, module_name, &report_path);
+                    }
+                    return Ok(());
+                },
+            }
+        }
+    }
+
+    #[cfg(not(unix))]
+    let _ = time_report;
+
+    context.compile_to_file(kind, path);
+    if let Ok(Some(error)) = context.get_first_error() {
+        report_compile_error(cgcx, diag_handler, context, module_name, error);
+        return Err(FatalError);
+    }
+    Ok(())
+}
+
+fn report_compile_error(cgcx: &CodegenContext<GccCodegenBackend>, diag_handler: &Handler, context: &Context<'_>, module_name: &str, error: &str) {
+    let dir = GccOptions::new(&cgcx.opts).reproducer_dir(&cgcx.output_filenames);
+    let reproducer = ReproducerDump::dump(context, &dir, module_name, true);
+    diag_handler.emit_err(LibgccjitCompileError { module: module_name, error, reproducer: reproducer.as_note() });
+}
+
+#[cfg(unix)]
+fn report_process_failure(cgcx: &CodegenContext<GccCodegenBackend>, diag_handler: &Handler, context: &Context<'_>, module_name: &str, exit_code: Option<i32>) {
+    let dir = GccOptions::new(&cgcx.opts).reproducer_dir(&cgcx.output_filenames);
+    let reproducer = ReproducerDump::dump(context, &dir, module_name, true);
+    diag_handler.emit_err(CompilationProcessFailed { module: module_name, exit_code: exit_code.into(), reproducer: reproducer.as_note() });
+}
+
+#[cfg(unix)]
+fn report_process_error(diag_handler: &Handler, module_name: &str, operation: &'static str, error: io::Error) {
+    diag_handler.emit_err(CompilationProcessError { module: module_name, operation, error: error.to_string() });
+}
+
+#[cfg(unix)]
+pub(crate) unsafe fn redirect_stderr(path: &Path) {
+    use std::os::unix::ffi::OsStrExt;
+
+    let path = CString::new(path.as_os_str().as_bytes()).expect("path to CString");
+    let fd = libc::open(path.as_ptr(), libc::O_WRONLY | libc::O_CREAT | libc::O_TRUNC, 0o644);
+    if fd != -1 {
+        libc::dup2(fd, libc::STDERR_FILENO);
+        libc::close(fd);
+    }
+}
+
 pub(crate) fn link(_cgcx: &CodegenContext<GccCodegenBackend>, _diag_handler: &Handler, mut _modules: Vec<ModuleCodegen<GccContext>>) -> Result<ModuleCodegen<GccContext>, FatalError> {
     unimplemented!();
 }
@@ -1,5 +1,4 @@
-use std::collections::HashSet;
-use std::env;
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 use std::time::Instant;
 
@@ -9,20 +8,27 @@ use gccjit::{
     GlobalKind, TargetInfo,
 };
 use rustc_middle::dep_graph;
-use rustc_middle::ty::TyCtxt;
+use rustc_middle::middle::codegen_fn_attrs::CodegenFnAttrFlags;
+use rustc_middle::ty::{Instance, TyCtxt};
 #[cfg(feature="master")]
 use rustc_middle::mir::mono::Visibility;
-use rustc_middle::mir::mono::Linkage;
+use rustc_middle::mir::mono::{Linkage, MonoItem};
 use rustc_codegen_ssa::{ModuleCodegen, ModuleKind};
 use rustc_codegen_ssa::base::maybe_create_entry_wrapper;
 use rustc_codegen_ssa::mono_item::MonoItemExt;
 use rustc_codegen_ssa::traits::DebugInfoMethods;
-use rustc_session::config::DebugInfo;
-use rustc_span::Symbol;
+use rustc_session::Session;
+use rustc_session::config::{BranchProtection, CFProtection, DebugInfo, PAuthKey};
+use rustc_span::{Span, Symbol};
+use rustc_target::spec::{CodeModel, FramePointer, PanicStrategy, RelocModel, SanitizerSet, StackProbeType, StackProtector};
 
 use crate::GccContext;
+use crate::attributes::target_cpu_options;
+use crate::back::timings::needs_time_report;
 use crate::builder::Builder;
-use crate::context::CodegenCx;
+use crate::context::{CodegenCx, ENTRY_WRAPPER_NAME};
+use crate::errors::{LibgccjitError, NakedFunctionStackProtection};
+use crate::options::GccOptions;
 
 #[cfg(feature="master")]
 pub fn visibility_to_gcc(linkage: Visibility) -> gccjit::Visibility {
@@ -33,35 +39,288 @@ pub fn visibility_to_gcc(linkage: Visibility) -> gccjit::Visibility {
     }
 }
 
-pub fn global_linkage_to_gcc(linkage: Linkage) -> GlobalKind {
+/// The naked functions of a module, which must have neither a stack protector nor stack probes.
+#[derive(Clone, Copy)]
+pub enum NakedFunctions {
+    None,
+    /// All the functions of the module are naked.
+    All,
+    /// The module contains both naked and other functions: this is the span of a naked one.
+    Some(Span),
+}
+
+/// Adds the options that depend on the session and apply to every module, including the
+/// allocator shim.
+pub fn add_session_options(context: &Context<'_>, sess: &Session, naked_functions: NakedFunctions) {
+    add_unwind_options(context, sess);
+    add_cpu_options(context, sess);
+    add_frame_pointer_options(context, sess);
+    add_stack_protector_options(context, sess, naked_functions);
+    add_stack_probe_options(context, sess, naked_functions);
+    add_control_flow_protection_options(context, sess);
+    add_relocation_options(context, sess);
+    add_bare_metal_options(context, sess);
+}
+
+/// Enables the exceptions only when panics unwind, so that the modules compiled with
+/// `-Cpanic=abort` don't have exception tables nor reference the personality function.
+fn add_unwind_options(context: &Context<'_>, sess: &Session) {
+    if sess.panic_strategy() == PanicStrategy::Unwind {
+        context.add_command_line_option("-fexceptions");
+        context.add_driver_option("-fexceptions");
+    }
+
+    // NOTE: unwind tables can still be required without exceptions (e.g. for backtraces or by
+    // -Cforce-unwind-tables).
+    if sess.must_emit_unwind_tables() {
+        context.add_command_line_option("-fasynchronous-unwind-tables");
+    }
+    else {
+        context.add_command_line_option("-fno-asynchronous-unwind-tables");
+        context.add_command_line_option("-fno-unwind-tables");
+    }
+}
+
+fn add_cpu_options(context: &Context<'_>, sess: &Session) {
+    for option in target_cpu_options(sess) {
+        context.add_command_line_option(&option);
+    }
+}
+
+/// NOTE: the frame pointers are set for the whole module since libgccjit cannot change the
+/// optimization options of a single function.
+fn add_frame_pointer_options(context: &Context<'_>, sess: &Session) {
+    let mut frame_pointer = sess.target.frame_pointer;
+    // NOTE: mcount relies on the frame pointer.
+    if sess.opts.unstable_opts.instrument_mcount || sess.opts.cg.force_frame_pointers == Some(true) {
+        frame_pointer = FramePointer::Always;
+    }
+
+    // NOTE: the leaf frame pointer options only exist on some architectures.
+    let has_leaf_option = matches!(&*sess.target.arch, "x86" | "x86_64" | "aarch64");
+    match frame_pointer {
+        FramePointer::Always => {
+            context.add_command_line_option("-fno-omit-frame-pointer");
+            if has_leaf_option {
+                context.add_command_line_option("-mno-omit-leaf-frame-pointer");
+            }
+        },
+        FramePointer::NonLeaf => {
+            context.add_command_line_option("-fno-omit-frame-pointer");
+            if has_leaf_option {
+                context.add_command_line_option("-momit-leaf-frame-pointer");
+            }
+        },
+        FramePointer::MayOmit => (),
+    }
+}
+
+fn add_stack_protector_options(context: &Context<'_>, sess: &Session, naked_functions: NakedFunctions) {
+    let option =
+        match sess.stack_protector() {
+            StackProtector::None => return,
+            StackProtector::Basic => "-fstack-protector",
+            StackProtector::Strong => "-fstack-protector-strong",
+            StackProtector::All => "-fstack-protector-all",
+        };
+
+    // NOTE: a naked function must not have a canary, but libgccjit doesn't support the
+    // no_stack_protector attribute, so the stack protector can only be disabled in the whole
+    // module.
+    if !can_protect_stack(sess, naked_functions, "the stack protector") {
+        return;
+    }
+
+    context.add_command_line_option(option);
+}
+
+fn add_stack_probe_options(context: &Context<'_>, sess: &Session, naked_functions: NakedFunctions) {
+    // NOTE: like LLVM, don't probe the stack with the sanitizers and the profilers that don't work
+    // well with them. The address sanitizer already detects stack overflows anyway.
+    if sess.opts.unstable_opts.sanitizer.intersects(SanitizerSet::ADDRESS | SanitizerSet::THREAD)
+        || sess.opts.cg.profile_generate.enabled() || sess.opts.unstable_opts.profile
+    {
+        return;
+    }
+
+    if sess.target.stack_probes == StackProbeType::None {
+        return;
+    }
+
+    // NOTE: like for the stack protector, the stack probes cannot be disabled in a single naked
+    // function.
+    if !can_protect_stack(sess, naked_functions, "stack probing") {
+        return;
+    }
+
+    // NOTE: GCC cannot call __rust_probestack, so the stack is always probed inline.
+    context.add_command_line_option("-fstack-clash-protection");
+}
+
+/// Whether `protection` can be enabled in a module with these naked functions.
+///
+/// NOTE: disabling it in the whole module would silently leave its other functions unprotected,
+/// so this is an error.
+fn can_protect_stack(sess: &Session, naked_functions: NakedFunctions, protection: &'static str) -> bool {
+    match naked_functions {
+        NakedFunctions::None => true,
+        NakedFunctions::All => false,
+        NakedFunctions::Some(span) => {
+            sess.emit_err(NakedFunctionStackProtection { span, protection });
+            false
+        },
+    }
+}
+
+/// NOTE: GCC also emits the GNU property notes marking the objects as compatible with these
+/// protections.
+fn add_control_flow_protection_options(context: &Context<'_>, sess: &Session) {
+    let arch = &*sess.target.arch;
+    if arch == "x86" || arch == "x86_64" {
+        // NOTE: some distributions enable -fcf-protection by default, so it is always set to
+        // follow the session.
+        let cf_protection =
+            match sess.opts.unstable_opts.cf_protection {
+                CFProtection::None => "none",
+                CFProtection::Branch => "branch",
+                CFProtection::Return => "return",
+                CFProtection::Full => "full",
+            };
+        context.add_command_line_option(&format!("-fcf-protection={}", cf_protection));
+    }
+
+    // NOTE: rustc only accepts -Zbranch-protection on AArch64.
+    if let Some(BranchProtection { bti, pac_ret }) = sess.opts.unstable_opts.branch_protection {
+        let mut protections = vec![];
+        if bti {
+            protections.push("bti");
+        }
+        if let Some(pac_ret) = pac_ret {
+            protections.push("pac-ret");
+            if pac_ret.leaf {
+                protections.push("leaf");
+            }
+            if pac_ret.key == PAuthKey::B {
+                protections.push("b-key");
+            }
+        }
+        if protections.is_empty() {
+            protections.push("none");
+        }
+        context.add_command_line_option(&format!("-mbranch-protection={}", protections.join("+")));
+    }
+}
+
+fn add_relocation_options(context: &Context<'_>, sess: &Session) {
+    match sess.relocation_model() {
+        RelocModel::Static => {
+            context.add_command_line_option("-fno-pic");
+            context.add_command_line_option("-fno-pie");
+        },
+        RelocModel::Pic => context.add_command_line_option("-fPIC"),
+        RelocModel::Pie => context.add_command_line_option("-fPIE"),
+        // NOTE: -mdynamic-no-pic only exists on Darwin, so this is compiled like the static
+        // relocation model, except for the thread-local variables.
+        RelocModel::DynamicNoPic => context.add_command_line_option("-fno-pic"),
+        // NOTE: an error is reported by CodegenBackend::init.
+        RelocModel::Ropi | RelocModel::Rwpi | RelocModel::RopiRwpi => (),
+    }
+
+    if let Some(code_model) = sess.code_model() {
+        let riscv = sess.target.arch.starts_with("riscv");
+        let code_model =
+            match code_model {
+                CodeModel::Tiny => "tiny",
+                CodeModel::Small if riscv => "medlow",
+                CodeModel::Small => "small",
+                CodeModel::Kernel => "kernel",
+                CodeModel::Medium if riscv => "medany",
+                CodeModel::Medium => "medium",
+                CodeModel::Large => "large",
+            };
+        context.add_command_line_option(&format!("-mcmodel={}", code_model));
+    }
+
+    if !sess.needs_plt() {
+        context.add_command_line_option("-fno-plt");
+    }
+}
+
+fn add_bare_metal_options(context: &Context<'_>, sess: &Session) {
+    let arch = &*sess.target.arch;
+    if sess.target.disable_redzone && (arch == "x86" || arch == "x86_64") {
+        context.add_command_line_option("-mno-red-zone");
+    }
+
+    if sess.opts.unstable_opts.no_jump_tables {
+        context.add_command_line_option("-fno-jump-tables");
+    }
+}
+
+/// Instruments the module for gcov with -Zprofile.
+///
+/// NOTE: unlike LLVM, GCC writes the coverage data of every module in its own .gcno and .gcda
+/// files. They are named after -dumpbase, which also changes the location of the GCC dumps.
+fn add_profile_options(context: &Context<'_>, tcx: TyCtxt<'_>, module_name: &str) {
+    if !tcx.sess.opts.unstable_opts.profile {
+        return;
+    }
+
+    context.add_command_line_option("-fprofile-arcs");
+    context.add_command_line_option("-ftest-coverage");
+
+    let output_filenames = tcx.output_filenames(());
+    let note_base = output_filenames.temp_path_ext("", Some(module_name));
+    let data_base =
+        match tcx.sess.opts.unstable_opts.profile_emit {
+            Some(ref path) => {
+                // NOTE: with -Zprofile-emit, the .gcda files are named after the given path and
+                // the .gcno files stay in the output directory.
+                let mut data_base = path.with_extension("").into_os_string();
+                data_base.push(".");
+                data_base.push(module_name);
+                let mut note_path = note_base.into_os_string();
+                note_path.push(".gcno");
+                context.add_command_line_option(&format!("-fprofile-note={}", note_path.to_string_lossy()));
+                data_base.into()
+            },
+            None => note_base,
+        };
+    // NOTE: -dumpbase also names the files written by -gcc-dump-rtl and -gcc-dump-tree-all, so with
+    // -Zprofile they are named like the .gcda files and written in the same directory.
+    context.add_command_line_option("-dumpbase");
+    context.add_command_line_option(&data_base.to_string_lossy());
+}
+
+pub fn global_linkage_to_gcc(cx: &CodegenCx<'_, '_>, linkage: Linkage) -> GlobalKind {
     match linkage {
         Linkage::External => GlobalKind::Imported,
         Linkage::AvailableExternally => GlobalKind::Imported,
-        Linkage::LinkOnceAny => unimplemented!(),
-        Linkage::LinkOnceODR => unimplemented!(),
-        Linkage::WeakAny => unimplemented!(),
-        Linkage::WeakODR => unimplemented!(),
-        Linkage::Appending => unimplemented!(),
+        Linkage::LinkOnceAny | Linkage::LinkOnceODR | Linkage::WeakAny | Linkage::WeakODR | Linkage::Appending | Linkage::Common => {
+            cx.report_unsupported(&format!("{:?} linkage for statics", linkage));
+            GlobalKind::Imported
+        },
         Linkage::Internal => GlobalKind::Internal,
         Linkage::Private => GlobalKind::Internal,
         Linkage::ExternalWeak => GlobalKind::Imported, // TODO(antoyo): should be weak linkage.
-        Linkage::Common => unimplemented!(),
     }
 }
 
-pub fn linkage_to_gcc(linkage: Linkage) -> FunctionType {
+pub fn linkage_to_gcc(cx: &CodegenCx<'_, '_>, linkage: Linkage) -> FunctionType {
     match linkage {
         Linkage::External => FunctionType::Exported,
         Linkage::AvailableExternally => FunctionType::Extern,
-        Linkage::LinkOnceAny => unimplemented!(),
-        Linkage::LinkOnceODR => unimplemented!(),
         Linkage::WeakAny => FunctionType::Exported, // FIXME(antoyo): should be similar to linkonce.
-        Linkage::WeakODR => unimplemented!(),
-        Linkage::Appending => unimplemented!(),
+        Linkage::LinkOnceAny | Linkage::LinkOnceODR | Linkage::WeakODR | Linkage::Appending | Linkage::Common => {
+            cx.report_unsupported(&format!("{:?} linkage for functions", linkage));
+            FunctionType::Exported
+        },
+        Linkage::ExternalWeak => {
+            cx.report_unsupported(&format!("{:?} linkage for functions", linkage));
+            FunctionType::Extern
+        },
         Linkage::Internal => FunctionType::Internal,
         Linkage::Private => FunctionType::Internal,
-        Linkage::ExternalWeak => unimplemented!(),
-        Linkage::Common => unimplemented!(),
     }
 }
 
@@ -86,18 +345,47 @@ pub fn compile_codegen_unit(tcx: TyCtxt<'_>, cgu_name: Symbol, target_info: Arc<
 
     fn module_codegen(tcx: TyCtxt<'_>, (cgu_name, target_info): (Symbol, Arc<TargetInfo>)) -> ModuleCodegen<GccContext> {
         let cgu = tcx.codegen_unit(cgu_name);
+        let options = GccOptions::new(&tcx.sess.opts);
+        let module_name = cgu_name.as_str();
         // Instantiate monomorphizations without filling out definitions yet...
         //let llvm_module = ModuleLlvm::new(tcx, &cgu_name.as_str());
         let context = Context::default();
 
-        context.add_command_line_option("-fexceptions");
-        context.add_driver_option("-fexceptions");
+        let mono_items = cgu.items_in_deterministic_order(tcx);
+        let functions = mono_items.iter()
+            .filter_map(|&(mono_item, _)| match mono_item {
+                MonoItem::Fn(instance) => Some(instance),
+                _ => None,
+            });
+        let is_naked = |instance: &Instance<'_>| tcx.codegen_fn_attrs(instance.def_id()).flags.contains(CodegenFnAttrFlags::NAKED);
+        let naked_functions =
+            match functions.clone().find(is_naked) {
+                None => NakedFunctions::None,
+                Some(_) if functions.clone().all(|instance| is_naked(&instance)) => NakedFunctions::All,
+                Some(instance) => NakedFunctions::Some(tcx.def_span(instance.def_id())),
+            };
+        add_session_options(&context, tcx.sess, naked_functions);
 
-        let disabled_features: HashSet<_> = tcx.sess.opts.cg.target_feature.split(',')
-            .filter(|feature| feature.starts_with('-'))
-            .map(|string| &string[1..])
+        // NOTE: the features of the target spec can be overridden by -Ctarget-feature.
+        let mut target_features = HashMap::new();
+        for feature in tcx.sess.target.features.split(',').chain(tcx.sess.opts.cg.target_feature.split(',')) {
+            if let Some(feature) = feature.strip_prefix('+') {
+                target_features.insert(feature, true);
+            }
+            else if let Some(feature) = feature.strip_prefix('-') {
+                target_features.insert(feature, false);
+            }
+        }
+        let disabled_features: HashSet<_> = target_features.iter()
+            .filter(|&(_, &enabled)| !enabled)
+            .map(|(&feature, _)| feature)
             .collect();
 
+        // NOTE: kernel and bare-metal targets disable the floating-point and SIMD registers.
+        let soft_float = target_features.get("soft-float") == Some(&true);
+        let general_regs_only = soft_float
+            || ["sse", "sse2", "neon", "fp-armv8"].iter().any(|feature| disabled_features.contains(feature));
+
         let add_cpu_feature_flag = |feature: &str| {
             // FIXME(antoyo): some tests cause a segfault in GCC when not enabling all these
             // features.
@@ -106,26 +394,86 @@ pub fn compile_codegen_unit(tcx: TyCtxt<'_>, cgu_name: Symbol, target_info: Arc<
             }
         };
 
-        // TODO(antoyo): only set on x86 platforms.
-        context.add_command_line_option("-masm=intel");
+        let target_arch = &*tcx.sess.target.arch;
+        if target_arch == "x86" || target_arch == "x86_64" {
+            context.add_command_line_option("-masm=intel");
+        }
 
-        let features = ["sse2", "avx", "avx2", "sha", "fma", "gfni", "f16c", "aes", "bmi2", "rtm",
-            "vaes", "vpclmulqdq", "xsavec",
-        ];
+        if general_regs_only {
+            match target_arch {
+                "x86" | "x86_64" => {
+                    context.add_command_line_option("-mgeneral-regs-only");
+                    if soft_float {
+                        context.add_command_line_option("-msoft-float");
+                    }
+                },
+                "aarch64" => context.add_command_line_option("-mgeneral-regs-only"),
+                "arm" if soft_float => context.add_command_line_option("-mfloat-abi=soft"),
+                _ => (),
+            }
+        }
+        else if target_arch == "x86" || target_arch == "x86_64" {
+            let features = ["sse2", "avx", "avx2", "sha", "fma", "gfni", "f16c", "aes", "bmi2", "rtm",
+                "vaes", "vpclmulqdq", "xsavec",
+            ];
 
-        for feature in &features {
-            add_cpu_feature_flag(feature);
+            for feature in &features {
+                add_cpu_feature_flag(feature);
+            }
+
+            // TODO(antoyo): only add the following cli arguments if the feature is supported.
+            context.add_command_line_option("-mpclmul");
+            context.add_command_line_option("-mfma4");
+            context.add_command_line_option("-mbmi");
+            //context.add_command_line_option("-mavxvnni"); // The CI doesn't support this option.
         }
 
-        // TODO(antoyo): only add the following cli arguments if the feature is supported.
-        context.add_command_line_option("-mpclmul");
-        context.add_command_line_option("-mfma4");
-        context.add_command_line_option("-m64");
-        context.add_command_line_option("-mbmi");
-        //context.add_command_line_option("-mavxvnni"); // The CI doesn't support this option.
+        // NOTE: this version of rustc doesn't have -Zretpoline, so, like with LLVM, the retpolines
+        // are enabled with the target features (e.g. -Ctarget-feature=+retpoline-external-thunk).
+        // The return thunks don't have such a feature, so they must be enabled by passing
+        // -mfunction-return=thunk-extern with -Cllvm-args.
+        if target_arch == "x86" || target_arch == "x86_64" {
+            let is_enabled = |feature: &str| target_features.get(feature) == Some(&true);
+            let indirect_branch =
+                if is_enabled("retpoline-external-thunk") {
+                    Some("thunk-extern")
+                }
+                else if ["retpoline", "retpoline-indirect-calls", "retpoline-indirect-branches"].iter().any(|feature| is_enabled(feature)) {
+                    Some("thunk")
+                }
+                else {
+                    None
+                };
+            if let Some(indirect_branch) = indirect_branch {
+                context.add_command_line_option(&format!("-mindirect-branch={}", indirect_branch));
+                // NOTE: the thunks (e.g. __x86_indirect_thunk_rax) take the target in a register.
+                context.add_command_line_option("-mindirect-branch-register");
+                // NOTE: like LLVM, don't generate jump tables since they use indirect branches.
+                context.add_command_line_option("-fno-jump-tables");
+            }
+        }
+
+        match target_arch {
+            "x86_64" => context.add_command_line_option("-m64"),
+            "x86" => {
+                // NOTE: the driver also needs this option in order to assemble a 32-bit object
+                // with a multilib GCC.
+                context.add_command_line_option("-m32");
+                context.add_driver_option("-m32");
+                // NOTE: Rust floats must not use the extended precision of the x87 FPU, so
+                // use SSE for the computations, like LLVM does, when it's available.
+                if !general_regs_only && !disabled_features.contains("sse2") {
+                    context.add_command_line_option("-mfpmath=sse");
+                }
+            },
+            _ => (),
+        }
 
-        for arg in &tcx.sess.opts.cg.llvm_args {
-            context.add_command_line_option(arg);
+        for option in &options.command_line_options {
+            context.add_command_line_option(option);
+        }
+        for option in &options.driver_options {
+            context.add_driver_option(option);
         }
         // NOTE: This is needed to compile the file src/intrinsic/archs.rs during a bootstrap of rustc.
         context.add_command_line_option("-fno-var-tracking-assignments");
@@ -141,41 +489,88 @@ pub fn compile_codegen_unit(tcx: TyCtxt<'_>, cgu_name: Symbol, target_info: Arc<
             context.add_command_line_option("-fdata-sections");
         }
 
-        if env::var("CG_GCCJIT_DUMP_RTL").as_deref() == Ok("1") {
+        // NOTE: the frame pointers required by mcount are kept by add_session_options.
+        if tcx.sess.opts.unstable_opts.instrument_mcount {
+            context.add_command_line_option("-pg");
+            // NOTE: call __fentry__ before the prologue instead of mcount after it, which is what
+            // ftrace in the kernel expects.
+            if target_arch == "x86" || target_arch == "x86_64" {
+                context.add_command_line_option("-mfentry");
+            }
+        }
+        // NOTE: this version of rustc has neither #[patchable_function_entry] nor
+        // -Zpatchable-function-entry, so -fpatchable-function-entry=N,M must be passed with
+        // -Cllvm-args.
+
+        add_profile_options(&context, tcx, module_name);
+
+        if needs_time_report(&tcx.sess.opts, &tcx.prof) {
+            context.add_command_line_option("-ftime-report");
+        }
+
+        if options.dump_rtl.matches(module_name) {
             context.add_command_line_option("-fdump-rtl-vregs");
         }
-        if env::var("CG_GCCJIT_DUMP_TREE_ALL").as_deref() == Ok("1") {
+        if options.dump_tree_all.matches(module_name) {
             context.add_command_line_option("-fdump-tree-all");
         }
-        if env::var("CG_GCCJIT_DUMP_CODE").as_deref() == Ok("1") {
+        if options.dump_code.matches(module_name) {
             context.set_dump_code_on_compile(true);
         }
-        if env::var("CG_GCCJIT_DUMP_GIMPLE").as_deref() == Ok("1") {
+        if options.dump_gimple.matches(module_name) {
             context.set_dump_initial_gimple(true);
         }
         context.set_debug_info(true);
-        if env::var("CG_GCCJIT_DUMP_EVERYTHING").as_deref() == Ok("1") {
+        if options.dump_everything.matches(module_name) {
             context.set_dump_everything(true);
         }
-        if env::var("CG_GCCJIT_KEEP_INTERMEDIATES").as_deref() == Ok("1") {
+        if options.keep_intermediates.matches(module_name) {
             context.set_keep_intermediates(true);
         }
 
         // NOTE: The codegen generates unrechable blocks.
         context.set_allow_unreachable_blocks(true);
 
+        let mut error_reported = false;
+        let mut libgccjit_error = false;
         {
-            let cx = CodegenCx::new(&context, cgu, tcx, target_info.supports_128bit_int());
+            // NOTE: the target info is the one of the host GCC, but __int128 is only available on
+            // 64-bit targets.
+            let supports_128bit_integers = target_info.supports_128bit_int() && tcx.sess.target.pointer_width >= 64
+                && !options.emulate_128bit_integers;
+            let sse_registers_disabled = general_regs_only && target_arch == "x86_64";
+            let cx = CodegenCx::new(&context, cgu, tcx, supports_128bit_integers, sse_registers_disabled, &options);
+
+            // NOTE: libgccjit records the first error that happens when building the context, so
+            // checking it after every item allows pointing to the item that caused it.
+            // Errors following an unsupported feature are most likely caused by it, so they are
+            // not reported.
+            let mut check_gccjit_error = |cx: &CodegenCx<'_, '_>, mono_item| {
+                if cx.unsupported_feature_reported.get() {
+                    error_reported = true;
+                }
+                else if !error_reported {
+                    libgccjit_error = report_gccjit_error(cx, mono_item);
+                    error_reported = libgccjit_error;
+                }
+            };
 
-            let mono_items = cgu.items_in_deterministic_order(tcx);
             for &(mono_item, (linkage, visibility)) in &mono_items {
+                cx.current_item.set(Some(mono_item));
+                cx.current_span.set(None);
                 mono_item.predefine::<Builder<'_, '_, '_>>(&cx, linkage, visibility);
+                check_gccjit_error(&cx, mono_item);
             }
 
             // ... and now that we have everything pre-defined, fill out those definitions.
             for &(mono_item, _) in &mono_items {
+                cx.current_item.set(Some(mono_item));
+                cx.current_span.set(None);
                 mono_item.define::<Builder<'_, '_, '_>>(&cx);
+                check_gccjit_error(&cx, mono_item);
             }
+            cx.current_item.set(None);
+            cx.current_span.set(None);
 
             // If this codegen unit contains the main function, also create the
             // wrapper here
@@ -185,12 +580,32 @@ pub fn compile_codegen_unit(tcx: TyCtxt<'_>, cgu_name: Symbol, target_info: Arc<
             if cx.sess().opts.debuginfo != DebugInfo::None {
                 cx.debuginfo_finalize();
             }
+
+            // NOTE: the allocator shim is compiled in another context, so it is not instrumented.
+            if options.instrument_functions {
+                context.add_command_line_option("-finstrument-functions");
+                // NOTE: libgccjit doesn't support the no_instrument_function attribute, so the
+                // functions are excluded by their symbol.
+                let mut excluded = cx.no_instrument_functions.take();
+                excluded.sort();
+                excluded.dedup();
+                if cx.functions.borrow().contains_key(ENTRY_WRAPPER_NAME) {
+                    excluded.push(ENTRY_WRAPPER_NAME.to_string());
+                }
+                if !excluded.is_empty() {
+                    context.add_command_line_option(&format!("-finstrument-functions-exclude-function-list={}", excluded.join(",")));
+                }
+            }
+
+            error_reported |= cx.unsupported_feature_reported.get();
         }
 
         ModuleCodegen {
             name: cgu_name.to_string(),
             module_llvm: GccContext {
-                context
+                context,
+                error_reported,
+                libgccjit_error,
             },
             kind: ModuleKind::Regular,
         }
@@ -198,3 +613,32 @@ pub fn compile_codegen_unit(tcx: TyCtxt<'_>, cgu_name: Symbol, target_info: Arc<
 
     (module, cost)
 }
+
+/// Reports the first error recorded by libgccjit, if any, as a diagnostic pointing to the item
+/// that was being codegened.
+///
+/// NOTE: the reproducer of the module is dumped later by the worker compiling it, since this is
+/// done in a forked process and forking the codegen thread while the other threads might hold
+/// locks is not safe.
+fn report_gccjit_error<'tcx>(cx: &CodegenCx<'_, 'tcx>, mono_item: MonoItem<'tcx>) -> bool {
+    let error =
+        match cx.context.get_first_error() {
+            Ok(Some(error)) => error,
+            _ => return false,
+        };
+
+    cx.tcx.sess.emit_err(LibgccjitError {
+        span: mono_item_span(cx.tcx, mono_item),
+        item: mono_item.to_string(),
+        error,
+    });
+    true
+}
+
+pub fn mono_item_span(tcx: TyCtxt<'_>, mono_item: MonoItem<'_>) -> Span {
+    match mono_item {
+        MonoItem::Fn(instance) => tcx.def_span(instance.def_id()),
+        MonoItem::Static(def_id) => tcx.def_span(def_id),
+        MonoItem::GlobalAsm(item_id) => tcx.def_span(item_id.owner_id.to_def_id()),
+    }
+}
@@ -51,6 +51,7 @@ use rustc_target::abi::{
 };
 use rustc_target::spec::{HasTargetSpec, Target};
 
+use crate::abi::FnAbiGccExt;
 use crate::common::{SignType, TypeReflection, type_is_pointer};
 use crate::context::CodegenCx;
 use crate::intrinsic::llvm;
@@ -82,6 +83,54 @@ impl<'a, 'gcc, 'tcx> Builder<'a, 'gcc, 'tcx> {
         }
     }
 
+    /// Ends the current block by executing `try_block` and then jumping to `then`.
+    /// If an exception is thrown in `try_block`, the execution continues in the landing pad `catch`.
+    #[cfg(feature="master")]
+    pub fn end_with_try(&mut self, try_block: Block<'gcc>, then: Block<'gcc>, catch: Block<'gcc>) {
+        try_block.end_with_jump(None, then);
+
+        if self.cleanup_blocks.borrow().contains(&catch) {
+            self.block.add_try_finally(None, try_block, catch);
+        }
+        else {
+            self.block.add_try_catch(None, try_block, catch);
+        }
+
+        self.block.end_with_jump(None, then);
+    }
+
+    /// Returns the exception object and the selector value of the landing pad starting at the
+    /// current block.
+    #[cfg(feature="master")]
+    fn landing_pad_values(&mut self, pers_fn: RValue<'gcc>) -> (RValue<'gcc>, RValue<'gcc>) {
+        self.set_personality_fn(pers_fn);
+
+        let zero = self.cx.context.new_rvalue_zero(self.int_type);
+        let eh_pointer_builtin = self.cx.context.get_target_builtin_function("__builtin_eh_pointer");
+        let ptr = self.cx.context.new_call(None, eh_pointer_builtin, &[zero]);
+        let ptr = self.cx.context.new_cast(None, ptr, self.u8_type.make_pointer());
+
+        // NOTE: the selector is the index of the action of the landing pad that was chosen by the
+        // personality function, like the second value returned by the landingpad instruction of
+        // LLVM.
+        let eh_filter_builtin = self.cx.context.get_target_builtin_function("__builtin_eh_filter");
+        let selector = self.cx.context.new_call(None, eh_filter_builtin, &[zero]);
+        let selector = self.cx.context.new_cast(None, selector, self.i32_type);
+
+        (ptr, selector)
+    }
+
+    /// Reports an unsupported feature and returns a dummy value so that the codegen can continue.
+    pub fn unsupported_value(&self, feature: &str, typ: Type<'gcc>) -> RValue<'gcc> {
+        self.cx.report_unsupported(feature);
+        self.current_func().new_local(None, typ, "unsupported").to_rvalue()
+    }
+
+    #[cfg(not(feature="master"))]
+    fn vector_element_type(&self, vector: RValue<'gcc>) -> Type<'gcc> {
+        vector.get_type().unqualified().dyncast_vector().expect("vector type").get_element_type()
+    }
+
     fn atomic_extremum(&mut self, operation: ExtremumOperation, dst: RValue<'gcc>, src: RValue<'gcc>, order: AtomicOrdering) -> RValue<'gcc> {
         let size = src.get_type().get_size();
 
@@ -437,7 +486,14 @@ impl<'a, 'gcc, 'tcx> BuilderMethods<'a, 'tcx> for Builder<'a, 'gcc, 'tcx> {
     }
 
     fn ret_void(&mut self) {
-        self.llbb().end_with_void_return(None)
+        let func = self.current_func();
+        if self.struct_return_functions.borrow().contains(&func) {
+            let struct_return = self.struct_return_slot(func);
+            self.llbb().end_with_return(None, struct_return.to_rvalue());
+        }
+        else {
+            self.llbb().end_with_void_return(None)
+        }
     }
 
     fn ret(&mut self, mut value: RValue<'gcc>) {
@@ -465,6 +521,22 @@ impl<'a, 'gcc, 'tcx> BuilderMethods<'a, 'tcx> for Builder<'a, 'gcc, 'tcx> {
     fn switch(&mut self, value: RValue<'gcc>, default_block: Block<'gcc>, cases: impl ExactSizeIterator<Item = (u128, Block<'gcc>)>) {
         let mut gcc_cases = vec![];
         let typ = self.val_ty(value);
+        if self.is_non_native_int_type(typ) {
+            // NOTE: gcc cannot switch on an array, so compare the value with every case instead.
+            let func = self.current_func();
+            let value_local = func.new_local(None, typ, "switchValue");
+            self.llbb().add_assignment(None, value_local, value);
+            let value = value_local.to_rvalue();
+            for (on_val, dest) in cases {
+                let on_val = self.const_uint_big(typ, on_val);
+                let next_block = func.new_block("switch_next");
+                let condition = self.gcc_icmp(IntPredicate::IntEQ, value, on_val);
+                self.llbb().end_with_conditional(None, condition, dest, next_block);
+                self.switch_to_block(next_block);
+            }
+            self.llbb().end_with_jump(None, default_block);
+            return;
+        }
         for (on_val, dest) in cases {
             let on_val = self.const_uint_big(typ, on_val);
             gcc_cases.push(self.context.new_case(on_val, on_val, dest));
@@ -473,12 +545,12 @@ impl<'a, 'gcc, 'tcx> BuilderMethods<'a, 'tcx> for Builder<'a, 'gcc, 'tcx> {
     }
 
     #[cfg(feature="master")]
-    fn invoke(&mut self, typ: Type<'gcc>, fn_attrs: Option<&CodegenFnAttrs>, _fn_abi: Option<&FnAbi<'tcx, Ty<'tcx>>>, func: RValue<'gcc>, args: &[RValue<'gcc>], then: Block<'gcc>, catch: Block<'gcc>, _funclet: Option<&Funclet>) -> RValue<'gcc> {
+    fn invoke(&mut self, typ: Type<'gcc>, fn_attrs: Option<&CodegenFnAttrs>, fn_abi: Option<&FnAbi<'tcx, Ty<'tcx>>>, func: RValue<'gcc>, args: &[RValue<'gcc>], then: Block<'gcc>, catch: Block<'gcc>, _funclet: Option<&Funclet>) -> RValue<'gcc> {
         let try_block = self.current_func().new_block("try");
 
         let current_block = self.block.clone();
         self.block = try_block;
-        let call = self.call(typ, fn_attrs, None, func, args, None); // TODO(antoyo): use funclet here?
+        let call = self.call(typ, fn_attrs, fn_abi, func, args, None); // TODO(antoyo): use funclet here?
         self.block = current_block;
 
         let return_value = self.current_func()
@@ -486,28 +558,16 @@ impl<'a, 'gcc, 'tcx> BuilderMethods<'a, 'tcx> for Builder<'a, 'gcc, 'tcx> {
 
         try_block.add_assignment(None, return_value, call);
 
-        try_block.end_with_jump(None, then);
-
-        if self.cleanup_blocks.borrow().contains(&catch) {
-            self.block.add_try_finally(None, try_block, catch);
-        }
-        else {
-            self.block.add_try_catch(None, try_block, catch);
-        }
-
-        self.block.end_with_jump(None, then);
+        self.end_with_try(try_block, then, catch);
 
         return_value.to_rvalue()
     }
 
     #[cfg(not(feature="master"))]
     fn invoke(&mut self, typ: Type<'gcc>, fn_attrs: &CodegenFnAttrs, fn_abi: Option<&FnAbi<'tcx, Ty<'tcx>>>, func: RValue<'gcc>, args: &[RValue<'gcc>], then: Block<'gcc>, catch: Block<'gcc>, _funclet: Option<&Funclet>) -> RValue<'gcc> {
-        let call_site = self.call(typ, fn_attrs, None, func, args, None);
+        let call_site = self.call(typ, fn_attrs, fn_abi, func, args, None);
         let condition = self.context.new_rvalue_from_int(self.bool_type, 1);
         self.llbb().end_with_conditional(None, condition, then, catch);
-        if let Some(_fn_abi) = fn_abi {
-            // TODO(bjorn3): Apply function attributes
-        }
         call_site
     }
 
@@ -733,7 +793,7 @@ impl<'a, 'gcc, 'tcx> BuilderMethods<'a, 'tcx> for Builder<'a, 'gcc, 'tcx> {
     }
 
     fn byte_array_alloca(&mut self, _len: RValue<'gcc>, _align: Align) -> RValue<'gcc> {
-        unimplemented!();
+        self.unsupported_value("dynamically-sized stack allocation", self.u8_type.make_pointer())
     }
 
     fn load(&mut self, pointee_ty: Type<'gcc>, ptr: RValue<'gcc>, align: Align) -> RValue<'gcc> {
@@ -1046,7 +1106,7 @@ impl<'a, 'gcc, 'tcx> BuilderMethods<'a, 'tcx> for Builder<'a, 'gcc, 'tcx> {
                 self.bitcast(value, dest_ty)
             },
             (true, true) => self.cx.context.new_cast(None, value, dest_ty),
-            (true, false) => unimplemented!(),
+            (true, false) => self.unsupported_value("pointer to integer cast in `pointercast`", dest_ty),
         }
     }
 
@@ -1124,8 +1184,8 @@ impl<'a, 'gcc, 'tcx> BuilderMethods<'a, 'tcx> for Builder<'a, 'gcc, 'tcx> {
     }
 
     #[allow(dead_code)]
-    fn va_arg(&mut self, _list: RValue<'gcc>, _ty: Type<'gcc>) -> RValue<'gcc> {
-        unimplemented!();
+    fn va_arg(&mut self, _list: RValue<'gcc>, ty: Type<'gcc>) -> RValue<'gcc> {
+        self.unsupported_value("`va_arg`", ty)
     }
 
     #[cfg(feature="master")]
@@ -1143,8 +1203,9 @@ impl<'a, 'gcc, 'tcx> BuilderMethods<'a, 'tcx> for Builder<'a, 'gcc, 'tcx> {
         self.context.new_array_access(None, array, idx).to_rvalue()
     }
 
-    fn vector_splat(&mut self, _num_elts: usize, _elt: RValue<'gcc>) -> RValue<'gcc> {
-        unimplemented!();
+    fn vector_splat(&mut self, num_elts: usize, elt: RValue<'gcc>) -> RValue<'gcc> {
+        let vector_type = self.context.new_vector_type(elt.get_type(), num_elts as u64);
+        self.unsupported_value("`vector_splat`", vector_type)
     }
 
     fn extract_value(&mut self, aggregate_value: RValue<'gcc>, idx: u64) -> RValue<'gcc> {
@@ -1230,22 +1291,11 @@ impl<'a, 'gcc, 'tcx> BuilderMethods<'a, 'tcx> for Builder<'a, 'gcc, 'tcx> {
 
     #[cfg(feature="master")]
     fn cleanup_landing_pad(&mut self, pers_fn: RValue<'gcc>) -> (RValue<'gcc>, RValue<'gcc>) {
-        self.set_personality_fn(pers_fn);
-
         // NOTE: insert the current block in a variable so that a later call to invoke knows to
         // generate a try/finally instead of a try/catch for this block.
         self.cleanup_blocks.borrow_mut().insert(self.block);
 
-        let eh_pointer_builtin = self.cx.context.get_target_builtin_function("__builtin_eh_pointer");
-        let zero = self.cx.context.new_rvalue_zero(self.int_type);
-        let ptr = self.cx.context.new_call(None, eh_pointer_builtin, &[zero]);
-
-        let value1_type = self.u8_type.make_pointer();
-        let ptr = self.cx.context.new_cast(None, ptr, value1_type);
-        let value1 = ptr;
-        let value2 = zero; // TODO(antoyo): set the proper value here (the type of exception?).
-
-        (value1, value2)
+        self.landing_pad_values(pers_fn)
     }
 
     #[cfg(not(feature="master"))]
@@ -1256,8 +1306,18 @@ impl<'a, 'gcc, 'tcx> BuilderMethods<'a, 'tcx> for Builder<'a, 'gcc, 'tcx> {
         (value1, value2)
     }
 
+    #[cfg(feature="master")]
+    fn filter_landing_pad(&mut self, pers_fn: RValue<'gcc>) -> (RValue<'gcc>, RValue<'gcc>) {
+        // NOTE: a filter landing pad is used when an exception must not go through a function
+        // (e.g. an `extern "C"` function that panics). Unlike a cleanup, it must stop the search
+        // phase of the unwinder so that the personality function doesn't find a handler in the
+        // callers. This is done by generating a try/catch instead of a try/finally: the catch-all
+        // clause is seen by the personality function as a handler, after which the MIR aborts.
+        self.landing_pad_values(pers_fn)
+    }
+
+    #[cfg(not(feature="master"))]
     fn filter_landing_pad(&mut self, pers_fn: RValue<'gcc>) -> (RValue<'gcc>, RValue<'gcc>) {
-        // TODO(antoyo): generate the correct landing pad
         self.cleanup_landing_pad(pers_fn)
     }
 
@@ -1276,15 +1336,15 @@ impl<'a, 'gcc, 'tcx> BuilderMethods<'a, 'tcx> for Builder<'a, 'gcc, 'tcx> {
     }
 
     fn cleanup_pad(&mut self, _parent: Option<RValue<'gcc>>, _args: &[RValue<'gcc>]) -> Funclet {
-        unimplemented!();
+        self.cx.report_unsupported("funclet-based exception handling (`cleanup_pad`)");
     }
 
     fn cleanup_ret(&mut self, _funclet: &Funclet, _unwind: Option<Block<'gcc>>) {
-        unimplemented!();
+        self.cx.report_unsupported("funclet-based exception handling (`cleanup_ret`)");
     }
 
     fn catch_pad(&mut self, _parent: RValue<'gcc>, _args: &[RValue<'gcc>]) -> Funclet {
-        unimplemented!();
+        self.cx.report_unsupported("funclet-based exception handling (`catch_pad`)");
     }
 
     fn catch_switch(
@@ -1293,7 +1353,7 @@ impl<'a, 'gcc, 'tcx> BuilderMethods<'a, 'tcx> for Builder<'a, 'gcc, 'tcx> {
         _unwind: Option<Block<'gcc>>,
         _handlers: &[Block<'gcc>],
     ) -> RValue<'gcc> {
-        unimplemented!();
+        self.unsupported_value("funclet-based exception handling (`catch_switch`)", self.int_type)
     }
 
     // Atomic Operations
@@ -1392,6 +1452,12 @@ impl<'a, 'gcc, 'tcx> BuilderMethods<'a, 'tcx> for Builder<'a, 'gcc, 'tcx> {
         args: &[RValue<'gcc>],
         funclet: Option<&Funclet>,
     ) -> RValue<'gcc> {
+        let struct_return_pointer =
+            match fn_abi {
+                Some(fn_abi) if fn_abi.returns_struct_popping_pointer(self.cx) => args.first().copied(),
+                _ => None,
+            };
+        let args = if struct_return_pointer.is_some() { &args[1..] } else { args };
         // FIXME(antoyo): remove when having a proper API.
         let gcc_func = unsafe { std::mem::transmute(func) };
         let call = if self.functions.borrow().values().any(|value| *value == gcc_func) {
@@ -1401,6 +1467,12 @@ impl<'a, 'gcc, 'tcx> BuilderMethods<'a, 'tcx> for Builder<'a, 'gcc, 'tcx> {
             // If it's a not function that was defined, it's a function pointer.
             self.function_ptr_call(typ, func, args, funclet)
         };
+        if let Some(struct_return_pointer) = struct_return_pointer {
+            // NOTE: the struct is returned by value in GCC: see `returns_struct_popping_pointer`.
+            let struct_return_pointer = self.pointercast(struct_return_pointer, call.get_type().make_pointer());
+            self.llbb().add_assignment(None, struct_return_pointer.dereference(None), call);
+            return self.context.new_rvalue_from_long(self.isize_type, 0);
+        }
         if let Some(_fn_abi) = fn_abi {
             // TODO(bjorn3): Apply function attributes
         }
@@ -1425,7 +1497,10 @@ impl<'a, 'gcc, 'tcx> BuilderMethods<'a, 'tcx> for Builder<'a, 'gcc, 'tcx> {
         // FIXME(bjorn3): implement
     }
 
-    fn set_span(&mut self, _span: Span) {}
+    fn set_span(&mut self, span: Span) {
+        // NOTE: only used to point to the code using a feature that is not supported.
+        self.cx.current_span.set(Some(span));
+    }
 
     fn from_immediate(&mut self, val: Self::Value) -> Self::Value {
         if self.cx().val_ty(val) == self.cx().type_i1() {
@@ -1452,7 +1527,7 @@ impl<'a, 'gcc, 'tcx> BuilderMethods<'a, 'tcx> for Builder<'a, 'gcc, 'tcx> {
     }
 
     fn instrprof_increment(&mut self, _fn_name: RValue<'gcc>, _hash: RValue<'gcc>, _num_counters: RValue<'gcc>, _index: RValue<'gcc>) {
-        unimplemented!();
+        self.cx.report_unsupported("instrumentation-based profiling");
     }
 }
 
@@ -1692,8 +1767,8 @@ impl<'a, 'gcc, 'tcx> Builder<'a, 'gcc, 'tcx> {
     }
 
     #[cfg(not(feature="master"))]
-    pub fn shuffle_vector(&mut self, _v1: RValue<'gcc>, _v2: RValue<'gcc>, _mask: RValue<'gcc>) -> RValue<'gcc> {
-        unimplemented!();
+    pub fn shuffle_vector(&mut self, v1: RValue<'gcc>, _v2: RValue<'gcc>, _mask: RValue<'gcc>) -> RValue<'gcc> {
+        self.unsupported_value("vector shuffle with this version of libgccjit", v1.get_type())
     }
 
     #[cfg(feature="master")]
@@ -1726,18 +1801,19 @@ impl<'a, 'gcc, 'tcx> Builder<'a, 'gcc, 'tcx> {
     }
 
     #[cfg(not(feature="master"))]
-    pub fn vector_reduce<F>(&mut self, _src: RValue<'gcc>, _op: F) -> RValue<'gcc>
+    pub fn vector_reduce<F>(&mut self, src: RValue<'gcc>, _op: F) -> RValue<'gcc>
     where F: Fn(RValue<'gcc>, RValue<'gcc>, &'gcc Context<'gcc>) -> RValue<'gcc>
     {
-        unimplemented!();
+        let element_type = self.vector_element_type(src);
+        self.unsupported_value("vector reduction with this version of libgccjit", element_type)
     }
 
     pub fn vector_reduce_op(&mut self, src: RValue<'gcc>, op: BinaryOp) -> RValue<'gcc> {
         self.vector_reduce(src, |a, b, context| context.new_binary_op(None, op, a.get_type(), a, b))
     }
 
-    pub fn vector_reduce_fadd_fast(&mut self, _acc: RValue<'gcc>, _src: RValue<'gcc>) -> RValue<'gcc> {
-        unimplemented!();
+    pub fn vector_reduce_fadd_fast(&mut self, acc: RValue<'gcc>, _src: RValue<'gcc>) -> RValue<'gcc> {
+        self.unsupported_value("fast floating-point vector reduction (`simd_reduce_add_unordered`)", acc.get_type())
     }
 
     #[cfg(feature="master")]
@@ -1752,12 +1828,12 @@ impl<'a, 'gcc, 'tcx> Builder<'a, 'gcc, 'tcx> {
     }
 
     #[cfg(not(feature="master"))]
-    pub fn vector_reduce_fadd(&mut self, _acc: RValue<'gcc>, _src: RValue<'gcc>) -> RValue<'gcc> {
-        unimplemented!();
+    pub fn vector_reduce_fadd(&mut self, acc: RValue<'gcc>, _src: RValue<'gcc>) -> RValue<'gcc> {
+        self.unsupported_value("vector reduction with this version of libgccjit", acc.get_type())
     }
 
-    pub fn vector_reduce_fmul_fast(&mut self, _acc: RValue<'gcc>, _src: RValue<'gcc>) -> RValue<'gcc> {
-        unimplemented!();
+    pub fn vector_reduce_fmul_fast(&mut self, acc: RValue<'gcc>, _src: RValue<'gcc>) -> RValue<'gcc> {
+        self.unsupported_value("fast floating-point vector reduction (`simd_reduce_mul_unordered`)", acc.get_type())
     }
 
     #[cfg(feature="master")]
@@ -1772,8 +1848,8 @@ impl<'a, 'gcc, 'tcx> Builder<'a, 'gcc, 'tcx> {
     }
 
     #[cfg(not(feature="master"))]
-    pub fn vector_reduce_fmul(&mut self, _acc: RValue<'gcc>, _src: RValue<'gcc>) -> RValue<'gcc> {
-        unimplemented!()
+    pub fn vector_reduce_fmul(&mut self, acc: RValue<'gcc>, _src: RValue<'gcc>) -> RValue<'gcc> {
+        self.unsupported_value("vector reduction with this version of libgccjit", acc.get_type())
     }
 
     // Inspired by Hacker's Delight min implementation.
@@ -1836,8 +1912,9 @@ impl<'a, 'gcc, 'tcx> Builder<'a, 'gcc, 'tcx> {
     }
 
     #[cfg(not(feature="master"))]
-    pub fn vector_reduce_fmin(&mut self, _src: RValue<'gcc>) -> RValue<'gcc> {
-        unimplemented!();
+    pub fn vector_reduce_fmin(&mut self, src: RValue<'gcc>) -> RValue<'gcc> {
+        let element_type = self.vector_element_type(src);
+        self.unsupported_value("vector reduction with this version of libgccjit", element_type)
     }
 
     pub fn vector_fmax(&mut self, a: RValue<'gcc>, b: RValue<'gcc>) -> RValue<'gcc> {
@@ -1860,8 +1937,9 @@ impl<'a, 'gcc, 'tcx> Builder<'a, 'gcc, 'tcx> {
     }
 
     #[cfg(not(feature="master"))]
-    pub fn vector_reduce_fmax(&mut self, _src: RValue<'gcc>) -> RValue<'gcc> {
-        unimplemented!();
+    pub fn vector_reduce_fmax(&mut self, src: RValue<'gcc>) -> RValue<'gcc> {
+        let element_type = self.vector_element_type(src);
+        self.unsupported_value("vector reduction with this version of libgccjit", element_type)
     }
 
     pub fn vector_select(&mut self, cond: RValue<'gcc>, then_val: RValue<'gcc>, else_val: RValue<'gcc>) -> RValue<'gcc> {
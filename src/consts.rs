@@ -72,7 +72,8 @@ impl<'gcc, 'tcx> StaticMethods for CodegenCx<'gcc, 'tcx> {
         let val_llty = self.val_ty(value);
         let value =
             if val_llty == self.type_i1() {
-                unimplemented!();
+                self.report_unsupported("boolean static initializer");
+                value
             }
             else {
                 value
@@ -135,7 +136,7 @@ impl<'gcc, 'tcx> StaticMethods for CodegenCx<'gcc, 'tcx> {
                 // happens to be zero. Instead, we should only check the value of defined bytes
                 // and set all undefined bytes to zero if this allocation is headed for the
                 // BSS.
-                unimplemented!();
+                self.report_unsupported("mutable statics on macOS");
             }
         }
 
@@ -143,7 +144,7 @@ impl<'gcc, 'tcx> StaticMethods for CodegenCx<'gcc, 'tcx> {
         // go into custom sections of the wasm executable.
         if self.tcx.sess.opts.target_triple.triple().starts_with("wasm32") {
             if let Some(_section) = attrs.link_section {
-                unimplemented!();
+                self.report_unsupported("`#[link_section]` on wasm");
             }
         } else {
             // TODO(antoyo): set link section.
@@ -266,7 +267,7 @@ impl<'gcc, 'tcx> CodegenCx<'gcc, 'tcx> {
                 // is actually present in the current crate. We can find out via the
                 // is_codegened_item query.
                 if !self.tcx.is_codegened_item(def_id) {
-                    unimplemented!();
+                    self.report_unsupported("dllimport of statics from other crates");
                 }
             }
         }
@@ -346,7 +347,7 @@ fn check_and_apply_linkage<'gcc, 'tcx>(cx: &CodegenCx<'gcc, 'tcx>, attrs: &Codeg
     let gcc_type = cx.layout_of(ty).gcc_type(cx);
     if let Some(linkage) = attrs.import_linkage {
         // Declare a symbol `foo` with the desired linkage.
-        let global1 = cx.declare_global_with_linkage(&sym, cx.type_i8(), base::global_linkage_to_gcc(linkage));
+        let global1 = cx.declare_global_with_linkage(&sym, cx.type_i8(), base::global_linkage_to_gcc(cx, linkage));
 
         // Declare an internal global `extern_with_linkage_foo` which
         // is initialized with the address of `foo`.  If `foo` is
@@ -9,16 +9,23 @@ use rustc_codegen_ssa::traits::{
 };
 use rustc_data_structures::base_n;
 use rustc_data_structures::fx::{FxHashMap, FxHashSet};
-use rustc_middle::span_bug;
-use rustc_middle::mir::mono::CodegenUnit;
+use rustc_middle::{bug, span_bug};
+use rustc_middle::mir::mono::{CodegenUnit, MonoItem};
 use rustc_middle::ty::{self, Instance, ParamEnv, PolyExistentialTraitRef, Ty, TyCtxt};
 use rustc_middle::ty::layout::{FnAbiError, FnAbiOf, FnAbiOfHelpers, FnAbiRequest, HasParamEnv, HasTyCtxt, LayoutError, TyAndLayout, LayoutOfHelpers};
 use rustc_session::Session;
-use rustc_span::{Span, source_map::respan};
+use rustc_span::{DUMMY_SP, Span, source_map::respan};
 use rustc_target::abi::{call::FnAbi, HasDataLayout, PointeeInfo, Size, TargetDataLayout, VariantIdx};
-use rustc_target::spec::{HasTargetSpec, Target, TlsModel};
+use rustc_target::spec::{HasTargetSpec, RelocModel, Target, TlsModel};
 
+use crate::base::mono_item_span;
 use crate::callee::get_fn;
+use crate::errors::UnsupportedFeature;
+use crate::options::GccOptions;
+
+/// Name of the C main wrapper when the functions are instrumented, so that it can be excluded from
+/// the instrumentation.
+pub const ENTRY_WRAPPER_NAME: &str = "__rustc_codegen_gcc_entry_wrapper";
 
 #[derive(Clone)]
 pub struct FuncSig<'gcc> {
@@ -69,6 +76,8 @@ pub struct CodegenCx<'gcc, 'tcx> {
     pub sizet_type: Type<'gcc>,
 
     pub supports_128bit_integers: bool,
+    /// Whether the SSE registers are disabled on x86_64, where the C ABI passes the floats in them.
+    pub sse_registers_disabled: bool,
 
     pub float_type: Type<'gcc>,
     pub double_type: Type<'gcc>,
@@ -92,6 +101,11 @@ pub struct CodegenCx<'gcc, 'tcx> {
     pub on_stack_params: RefCell<FxHashMap<FunctionPtrType<'gcc>, FxHashSet<usize>>>,
     /// Mapping from function to indexes of on stack parameters.
     pub on_stack_function_params: RefCell<FxHashMap<Function<'gcc>, FxHashSet<usize>>>,
+    /// Functions returning their value in GCC instead of through their first parameter: see
+    /// `FnAbiGccExt::returns_struct_popping_pointer`.
+    pub struct_return_functions: RefCell<FxHashSet<Function<'gcc>>>,
+    /// Local variable holding the return value of the functions in `struct_return_functions`.
+    pub struct_return_slots: RefCell<FxHashMap<Function<'gcc>, LValue<'gcc>>>,
 
     /// Cache of emitted const globals (value -> global)
     pub const_globals: RefCell<FxHashMap<RValue<'gcc>, RValue<'gcc>>>,
@@ -122,10 +136,26 @@ pub struct CodegenCx<'gcc, 'tcx> {
     pub structs_as_pointer: RefCell<FxHashSet<RValue<'gcc>>>,
 
     pub cleanup_blocks: RefCell<FxHashSet<Block<'gcc>>>,
+
+    /// Whether -fnon-call-exceptions was enabled for this codegen unit, which is needed for
+    /// inline asm that can unwind.
+    pub non_call_exceptions: Cell<bool>,
+
+    /// The item being codegened and the span of the statement being codegened, used to report
+    /// unsupported features.
+    pub current_item: Cell<Option<MonoItem<'tcx>>>,
+    pub current_span: Cell<Option<Span>>,
+    /// Whether an unsupported feature was reported for this codegen unit.
+    pub unsupported_feature_reported: Cell<bool>,
+
+    /// Paths of the functions that must not be instrumented, if -finstrument-functions is enabled.
+    pub instrument_functions_exclude: Option<Vec<String>>,
+    /// Symbols of the functions that must not be instrumented.
+    pub no_instrument_functions: RefCell<Vec<String>>,
 }
 
 impl<'gcc, 'tcx> CodegenCx<'gcc, 'tcx> {
-    pub fn new(context: &'gcc Context<'gcc>, codegen_unit: &'tcx CodegenUnit<'tcx>, tcx: TyCtxt<'tcx>, supports_128bit_integers: bool) -> Self {
+    pub fn new(context: &'gcc Context<'gcc>, codegen_unit: &'tcx CodegenUnit<'tcx>, tcx: TyCtxt<'tcx>, supports_128bit_integers: bool, sse_registers_disabled: bool, options: &GccOptions) -> Self {
         let check_overflow = tcx.sess.overflow_checks();
 
         let i8_type = context.new_c_type(CType::Int8t);
@@ -149,7 +179,7 @@ impl<'gcc, 'tcx> CodegenCx<'gcc, 'tcx> {
                 (i128_type, u128_type)
             };
 
-        let tls_model = to_gcc_tls_mode(tcx.sess.tls_model());
+        let tls_model = to_gcc_tls_mode(tcx.sess.tls_model(), tcx.sess.relocation_model());
 
         let float_type = context.new_type::<f32>();
         let double_type = context.new_type::<f64>();
@@ -166,13 +196,17 @@ impl<'gcc, 'tcx> CodegenCx<'gcc, 'tcx> {
         let ulonglong_type = context.new_c_type(CType::ULongLong);
         let sizet_type = context.new_c_type(CType::SizeT);
 
-        let isize_type = context.new_c_type(CType::LongLong);
-        let usize_type = context.new_c_type(CType::ULongLong);
+        let (isize_type, usize_type) =
+            match tcx.sess.target.pointer_width {
+                16 => (i16_type, u16_type),
+                32 => (i32_type, u32_type),
+                64 => (context.new_c_type(CType::LongLong), context.new_c_type(CType::ULongLong)),
+                width => bug!("unsupported pointer width {}", width),
+            };
         let bool_type = context.new_type::<bool>();
 
-        // TODO(antoyo): only have those assertions on x86_64.
-        assert_eq!(isize_type.get_size(), i64_type.get_size());
-        assert_eq!(usize_type.get_size(), u64_type.get_size());
+        assert_eq!(isize_type.get_size() as u64 * 8, tcx.data_layout.pointer_size.bits());
+        assert_eq!(usize_type.get_size() as u64 * 8, tcx.data_layout.pointer_size.bits());
 
         let mut functions = FxHashMap::default();
         let builtins = [
@@ -229,6 +263,7 @@ impl<'gcc, 'tcx> CodegenCx<'gcc, 'tcx> {
             sizet_type,
 
             supports_128bit_integers,
+            sse_registers_disabled,
 
             float_type,
             double_type,
@@ -238,6 +273,8 @@ impl<'gcc, 'tcx> CodegenCx<'gcc, 'tcx> {
             function_instances: Default::default(),
             on_stack_params: Default::default(),
             on_stack_function_params: Default::default(),
+            struct_return_functions: Default::default(),
+            struct_return_slots: Default::default(),
             vtables: Default::default(),
             const_globals: Default::default(),
             global_lvalues: Default::default(),
@@ -253,6 +290,12 @@ impl<'gcc, 'tcx> CodegenCx<'gcc, 'tcx> {
             pointee_infos: Default::default(),
             structs_as_pointer: Default::default(),
             cleanup_blocks: Default::default(),
+            non_call_exceptions: Cell::new(false),
+            current_item: Cell::new(None),
+            current_span: Cell::new(None),
+            unsupported_feature_reported: Cell::new(false),
+            instrument_functions_exclude: options.instrument_functions.then(|| options.instrument_functions_exclude.clone()),
+            no_instrument_functions: Default::default(),
         }
     }
 
@@ -302,6 +345,18 @@ impl<'gcc, 'tcx> CodegenCx<'gcc, 'tcx> {
         &self.tcx.sess
     }
 
+    /// Reports a feature that is not supported by this backend.
+    /// This doesn't abort the compilation so that all the unsupported features are reported at once.
+    pub fn report_unsupported(&self, feature: &str) {
+        self.unsupported_feature_reported.set(true);
+        let item = self.current_item.get();
+        let span = self.current_span.get()
+            .or_else(|| item.map(|item| mono_item_span(self.tcx, item)))
+            .unwrap_or(DUMMY_SP);
+        let item = item.map_or_else(|| self.codegen_unit.name().to_string(), |item| item.to_string());
+        self.sess().emit_err(UnsupportedFeature { span, feature, item });
+    }
+
     pub fn bitcast_if_needed(&self, value: RValue<'gcc>, expected_type: Type<'gcc>) -> RValue<'gcc> {
         if value.get_type() != expected_type {
             self.context.new_bitcast(None, value, expected_type)
@@ -432,16 +487,20 @@ impl<'gcc, 'tcx> MiscMethods<'tcx> for CodegenCx<'gcc, 'tcx> {
     }
 
     fn set_frame_pointer_type(&self, _llfn: RValue<'gcc>) {
-        // TODO(antoyo)
+        // NOTE: the frame pointers are set for the whole module in base::add_session_options.
     }
 
     fn apply_target_cpu_attr(&self, _llfn: RValue<'gcc>) {
-        // TODO(antoyo)
+        // NOTE: the CPU is selected for the whole module in base::add_session_options since no
+        // function can select another CPU.
     }
 
     fn declare_c_main(&self, fn_type: Self::Type) -> Option<Self::Function> {
         let entry_name = self.sess().target.entry_name.as_ref();
         if self.get_declared_value(entry_name).is_none() {
+            if self.instrument_functions_exclude.is_some() {
+                return Some(self.declare_instrumented_entry_fn(entry_name, fn_type));
+            }
             Some(self.declare_entry_fn(entry_name, fn_type, ()))
         }
         else {
@@ -453,6 +512,28 @@ impl<'gcc, 'tcx> MiscMethods<'tcx> for CodegenCx<'gcc, 'tcx> {
     }
 }
 
+impl<'gcc, 'tcx> CodegenCx<'gcc, 'tcx> {
+    /// Declares the C main wrapper under `ENTRY_WRAPPER_NAME` and makes the entry point an alias
+    /// to it.
+    ///
+    /// NOTE: GCC excludes functions from -finstrument-functions when one of the names given to
+    /// -finstrument-functions-exclude-function-list is a substring of their name. Excluding the
+    /// entry point itself would then exclude every function containing `main` in its symbol,
+    /// while no other symbol can contain the name of the wrapper.
+    fn declare_instrumented_entry_fn(&self, entry_name: &str, fn_type: Type<'gcc>) -> RValue<'gcc> {
+        let func = self.declare_entry_fn(ENTRY_WRAPPER_NAME, fn_type, ());
+        let target = &self.sess().target;
+        let prefix = if target.is_like_osx { "_" } else { "" };
+        let mut alias = format!(".globl {0}{1}\n.set {0}{1}, {0}{2}", prefix, entry_name, ENTRY_WRAPPER_NAME);
+        // NOTE: the alias must be a function symbol for the linker to keep the Thumb bit on ARM.
+        if !target.is_like_osx && !target.is_like_windows {
+            alias.push_str(&format!("\n.type {}, %function", entry_name));
+        }
+        self.context.add_top_level_asm(None, &alias);
+        func
+    }
+}
+
 impl<'gcc, 'tcx> HasTyCtxt<'tcx> for CodegenCx<'gcc, 'tcx> {
     fn tcx(&self) -> TyCtxt<'tcx> {
         self.tcx
@@ -534,8 +615,18 @@ impl<'b, 'tcx> CodegenCx<'b, 'tcx> {
     }
 }
 
-fn to_gcc_tls_mode(tls_model: TlsModel) -> gccjit::TlsModel {
+/// NOTE: unlike LLVM, where the symbols are marked dso_local to avoid going through the GOT,
+/// GCC decides how to address a symbol from the relocation model of the module: with the static
+/// one (-fno-pic), every symbol, including the ones from other crates, is addressed directly, the
+/// linker using copy relocations for the data of shared libraries. Only the thread-local variables
+/// need to be handled per symbol, through their TLS model.
+fn to_gcc_tls_mode(tls_model: TlsModel, reloc_model: RelocModel) -> gccjit::TlsModel {
     match tls_model {
+        // NOTE: like LLVM, the dynamic models are not needed with the static relocation model since
+        // the code ends up in an executable. GCC further relaxes it to local-exec for the
+        // thread-local variables defined in the executable.
+        TlsModel::GeneralDynamic | TlsModel::LocalDynamic if reloc_model == RelocModel::Static =>
+            gccjit::TlsModel::InitialExec,
         TlsModel::GeneralDynamic => gccjit::TlsModel::GlobalDynamic,
         TlsModel::LocalDynamic => gccjit::TlsModel::LocalDynamic,
         TlsModel::InitialExec => gccjit::TlsModel::InitialExec,
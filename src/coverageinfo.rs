@@ -19,7 +19,9 @@ impl<'a, 'gcc, 'tcx> CoverageInfoBuilderMethods<'tcx> for Builder<'a, 'gcc, 'tcx
         _instance: Instance<'tcx>,
         _function_source_hash: u64,
     ) -> bool {
-        unimplemented!();
+        self.cx.report_unsupported("coverage instrumentation");
+        // NOTE: returning false makes the caller skip the rest of the coverage instrumentation.
+        false
     }
 
     fn add_coverage_counter(&mut self, _instance: Instance<'tcx>, _id: CounterValueReference, _region: CodeRegion) -> bool {
@@ -44,7 +46,8 @@ impl<'gcc, 'tcx> CoverageInfoMethods<'tcx> for CodegenCx<'gcc, 'tcx> {
     }
 
     fn get_pgo_func_name_var(&self, _instance: Instance<'tcx>) -> RValue<'gcc> {
-        unimplemented!();
+        self.report_unsupported("coverage instrumentation");
+        self.context.new_null(self.u8_type.make_pointer())
     }
 
     /// Functions with MIR-based coverage are normally codegenned _only_ if
@@ -64,6 +67,6 @@ impl<'gcc, 'tcx> CoverageInfoMethods<'tcx> for CodegenCx<'gcc, 'tcx> {
     /// call. Since the function is never called, all other `CodeRegion`s can be
     /// added as `unreachable_region`s.
     fn define_unused_fn(&self, _def_id: DefId) {
-        unimplemented!();
+        self.report_unsupported("coverage instrumentation");
     }
 }
@@ -23,7 +23,7 @@ impl<'a, 'gcc, 'tcx> DebugInfoBuilderMethods for Builder<'a, 'gcc, 'tcx> {
         _indirect_offsets: &[Size],
         _fragment: Option<Range<Size>>,
     ) {
-        unimplemented!();
+        self.cx.report_unsupported("debuginfo for variables");
     }
 
     fn insert_reference_to_gdb_debug_scripts_section_global(&mut self) {
@@ -31,11 +31,12 @@ impl<'a, 'gcc, 'tcx> DebugInfoBuilderMethods for Builder<'a, 'gcc, 'tcx> {
     }
 
     fn set_var_name(&mut self, _value: RValue<'gcc>, _name: &str) {
-        unimplemented!();
+        // NOTE: the names of the values are only used to make the generated IR more readable, and
+        // GCC rvalues cannot be named.
     }
 
     fn set_dbg_loc(&mut self, _dbg_loc: Self::DILocation) {
-        unimplemented!();
+        self.cx.report_unsupported("debuginfo locations");
     }
 }
 
@@ -65,7 +66,7 @@ impl<'gcc, 'tcx> DebugInfoMethods<'tcx> for CodegenCx<'gcc, 'tcx> {
         _scope_metadata: Self::DIScope,
         _file: &SourceFile,
     ) -> Self::DIScope {
-        unimplemented!();
+        self.report_unsupported("debuginfo scopes");
     }
 
     fn debuginfo_finalize(&self) {
@@ -80,7 +81,7 @@ impl<'gcc, 'tcx> DebugInfoMethods<'tcx> for CodegenCx<'gcc, 'tcx> {
         _variable_kind: VariableKind,
         _span: Span,
     ) -> Self::DIVariable {
-        unimplemented!();
+        self.report_unsupported("debuginfo for variables");
     }
 
     fn dbg_scope_fn(
@@ -89,7 +90,7 @@ impl<'gcc, 'tcx> DebugInfoMethods<'tcx> for CodegenCx<'gcc, 'tcx> {
         _fn_abi: &FnAbi<'tcx, Ty<'tcx>>,
         _maybe_definition_llfn: Option<RValue<'gcc>>,
     ) -> Self::DIScope {
-        unimplemented!();
+        self.report_unsupported("debuginfo scopes");
     }
 
     fn dbg_loc(
@@ -98,6 +99,6 @@ impl<'gcc, 'tcx> DebugInfoMethods<'tcx> for CodegenCx<'gcc, 'tcx> {
         _inlined_at: Option<Self::DILocation>,
         _span: Span,
     ) -> Self::DILocation {
-        unimplemented!();
+        self.report_unsupported("debuginfo locations");
     }
 }
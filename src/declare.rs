@@ -81,6 +81,9 @@ impl<'gcc, 'tcx> CodegenCx<'gcc, 'tcx> {
         let (return_type, params, variadic, on_stack_param_indices) = fn_abi.gcc_type(self);
         let func = declare_raw_fn(self, name, () /*fn_abi.llvm_cconv()*/, return_type, &params, variadic);
         self.on_stack_function_params.borrow_mut().insert(func, on_stack_param_indices);
+        if fn_abi.returns_struct_popping_pointer(self) {
+            self.struct_return_functions.borrow_mut().insert(func);
+        }
         func
     }
 
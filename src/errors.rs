@@ -1,10 +1,16 @@
 use rustc_errors::{DiagnosticArgValue, IntoDiagnosticArg};
-use rustc_macros::Diagnostic;
+use rustc_macros::{Diagnostic, Subdiagnostic};
 use rustc_middle::ty::Ty;
 use rustc_span::{Span, Symbol};
 use std::borrow::Cow;
 
-struct ExitCode(Option<i32>);
+pub(crate) struct ExitCode(Option<i32>);
+
+impl From<Option<i32>> for ExitCode {
+    fn from(exit_code: Option<i32>) -> Self {
+        ExitCode(exit_code)
+    }
+}
 
 impl IntoDiagnosticArg for ExitCode {
     fn into_diagnostic_arg(self) -> DiagnosticArgValue<'static> {
@@ -211,10 +217,117 @@ pub(crate) struct InvalidMonomorphizationUnsupportedOperation<'a> {
     pub in_elem: Ty<'a>,
 }
 
+#[derive(Diagnostic)]
+#[diag(codegen_gcc_compilation_process_failed)]
+pub(crate) struct CompilationProcessFailed<'a> {
+    pub module: &'a str,
+    pub exit_code: ExitCode,
+    #[subdiagnostic]
+    pub reproducer: Reproducer<'a>,
+}
+
+#[derive(Diagnostic)]
+#[diag(codegen_gcc_compilation_process_error)]
+pub(crate) struct CompilationProcessError<'a> {
+    pub module: &'a str,
+    pub operation: &'static str,
+    pub error: String,
+}
+
+#[derive(Diagnostic)]
+#[diag(codegen_gcc_libgccjit_error)]
+#[note(codegen_gcc_libgccjit_error_note)]
+pub(crate) struct LibgccjitError<'a> {
+    #[primary_span]
+    pub span: Span,
+    pub item: String,
+    pub error: &'a str,
+}
+
+#[derive(Diagnostic)]
+#[diag(codegen_gcc_libgccjit_error_reproducer)]
+pub(crate) struct LibgccjitErrorReproducer<'a> {
+    pub module: &'a str,
+    #[subdiagnostic]
+    pub reproducer: Reproducer<'a>,
+}
+
+#[derive(Diagnostic)]
+#[diag(codegen_gcc_libgccjit_compile_error)]
+#[note(codegen_gcc_libgccjit_error_note)]
+pub(crate) struct LibgccjitCompileError<'a> {
+    pub module: &'a str,
+    pub error: &'a str,
+    #[subdiagnostic]
+    pub reproducer: Reproducer<'a>,
+}
+
+#[derive(Subdiagnostic)]
+pub(crate) enum Reproducer<'a> {
+    #[note(codegen_gcc_reproducer_dumped)]
+    Dumped {
+        path: &'a str,
+    },
+    #[note(codegen_gcc_reproducer_failed)]
+    Failed {
+        dir: &'a str,
+    },
+}
+
+#[derive(Diagnostic)]
+#[diag(codegen_gcc_unknown_option)]
+#[help]
+pub(crate) struct UnknownOption<'a> {
+    pub option: &'a str,
+}
+
+#[derive(Diagnostic)]
+#[diag(codegen_gcc_option_missing_value)]
+pub(crate) struct OptionMissingValue<'a> {
+    pub option: &'a str,
+}
+
+#[derive(Diagnostic)]
+#[diag(codegen_gcc_option_unexpected_value)]
+pub(crate) struct OptionUnexpectedValue<'a> {
+    pub option: &'a str,
+}
+
 #[derive(Diagnostic)]
 #[diag(codegen_gcc_lto_not_supported)]
 pub(crate) struct LTONotSupported;
 
+#[derive(Diagnostic)]
+#[diag(codegen_gcc_naked_function_stack_protection)]
+#[help]
+pub(crate) struct NakedFunctionStackProtection {
+    #[primary_span]
+    pub span: Span,
+    pub protection: &'static str,
+}
+
+#[derive(Diagnostic)]
+#[diag(codegen_gcc_native_cpu_not_supported)]
+pub(crate) struct NativeCpuNotSupported {
+    pub option: &'static str,
+}
+
+#[derive(Diagnostic)]
+#[diag(codegen_gcc_unsupported_feature)]
+#[note]
+pub(crate) struct UnsupportedFeature<'a> {
+    #[primary_span]
+    pub span: Span,
+    pub feature: &'a str,
+    pub item: String,
+}
+
+#[derive(Diagnostic)]
+#[diag(codegen_gcc_unsupported_reloc_model)]
+pub(crate) struct UnsupportedRelocModel<'a> {
+    pub reloc_model: &'a str,
+}
+
 #[derive(Diagnostic)]
 #[diag(codegen_gcc_unwinding_inline_asm)]
 pub(crate) struct UnwindingInlineAsm {
@@ -8,6 +8,7 @@ use gccjit::{ComparisonOp, FunctionType, RValue, ToRValue, Type, UnaryOp, Binary
 use rustc_codegen_ssa::common::{IntPredicate, TypeKind};
 use rustc_codegen_ssa::traits::{BackendTypes, BaseTypeMethods, BuilderMethods, OverflowOp};
 use rustc_middle::ty::Ty;
+use rustc_target::abi::Endian;
 
 use crate::builder::ToGccComp;
 use crate::{builder::Builder, common::{SignType, TypeReflection}, context::CodegenCx};
@@ -36,13 +37,11 @@ impl<'a, 'gcc, 'tcx> Builder<'a, 'gcc, 'tcx> {
             self.cx.context.new_unary_op(None, operation, typ, a)
         }
         else {
-            // TODO(antoyo): use __negdi2 and __negti2 instead?
             let element_type = typ.dyncast_array().expect("element type");
-            let values = [
+            self.from_low_high_rvalues(typ,
                 self.cx.context.new_unary_op(None, UnaryOp::BitwiseNegate, element_type, self.low(a)),
                 self.cx.context.new_unary_op(None, UnaryOp::BitwiseNegate, element_type, self.high(a)),
-            ];
-            self.cx.context.new_array_constructor(None, typ, &values)
+            )
         }
     }
 
@@ -52,9 +51,8 @@ impl<'a, 'gcc, 'tcx> Builder<'a, 'gcc, 'tcx> {
             self.cx.context.new_unary_op(None, UnaryOp::Minus, a.get_type(), a)
         }
         else {
-            let param_a = self.context.new_parameter(None, a_type, "a");
-            let func = self.context.new_function(None, FunctionType::Extern, a_type, &[param_a], "__negti2", false);
-            self.context.new_call(None, func, &[a])
+            // NOTE: __negti2 is not available on 32-bit targets.
+            self.gcc_sub(self.gcc_zero(a_type), a)
         }
     }
 
@@ -96,28 +94,24 @@ impl<'a, 'gcc, 'tcx> Builder<'a, 'gcc, 'tcx> {
 
             let result = func.new_local(None, a_type, "shiftResult");
 
-            let sixty_four = self.gcc_int(native_int_type, 64);
-            let sixty_three = self.gcc_int(native_int_type, 63);
+            let half_width = self.native_int_width(native_int_type);
+            let native_int_width = self.gcc_int(native_int_type, half_width);
+            let native_int_width_minus_one = self.gcc_int(native_int_type, half_width - 1);
             let zero = self.gcc_zero(native_int_type);
             let b = self.gcc_int_cast(b, native_int_type);
-            let condition = self.gcc_icmp(IntPredicate::IntNE, self.gcc_and(b, sixty_four), zero);
+            let condition = self.gcc_icmp(IntPredicate::IntNE, self.gcc_and(b, native_int_width), zero);
             self.llbb().end_with_conditional(None, condition, then_block, else_block);
 
-            // TODO(antoyo): take endianness into account.
-            let shift_value = self.gcc_sub(b, sixty_four);
+            let shift_value = self.gcc_sub(b, native_int_width);
             let high = self.high(a);
             let sign =
                 if a_type.is_signed(self) {
-                    high >> sixty_three
+                    high >> native_int_width_minus_one
                 }
                 else {
                     zero
                 };
-            let values = [
-                high >> shift_value,
-                sign,
-            ];
-            let array_value = self.context.new_array_constructor(None, a_type, &values);
+            let array_value = self.from_low_high_rvalues(a_type, high >> shift_value, sign);
             then_block.add_assignment(None, result, array_value);
             then_block.end_with_jump(None, after_block);
 
@@ -127,17 +121,13 @@ impl<'a, 'gcc, 'tcx> Builder<'a, 'gcc, 'tcx> {
             b0_block.add_assignment(None, result, a);
             b0_block.end_with_jump(None, after_block);
 
-            let shift_value = self.gcc_sub(sixty_four, b);
+            let shift_value = self.gcc_sub(native_int_width, b);
             // NOTE: cast low to its unsigned type in order to perform a logical right shift.
             let unsigned_type = native_int_type.to_unsigned(&self.cx);
             let casted_low = self.context.new_cast(None, self.low(a), unsigned_type);
             let shifted_low = casted_low >> self.context.new_cast(None, b, unsigned_type);
             let shifted_low = self.context.new_cast(None, shifted_low, native_int_type);
-            let values = [
-                (high << shift_value) | shifted_low,
-                high >> b,
-            ];
-            let array_value = self.context.new_array_constructor(None, a_type, &values);
+            let array_value = self.from_low_high_rvalues(a_type, (high << shift_value) | shifted_low, high >> b);
             actual_else_block.add_assignment(None, result, array_value);
             actual_else_block.end_with_jump(None, after_block);
 
@@ -166,19 +156,26 @@ impl<'a, 'gcc, 'tcx> Builder<'a, 'gcc, 'tcx> {
             self.context.new_binary_op(None, operation, a_type, a, b)
         }
         else {
-            let signed = a_type.is_compatible_with(self.i128_type);
-            let func_name =
-                match (operation, signed) {
-                    (BinaryOp::Plus, true) => "__rust_i128_add",
-                    (BinaryOp::Plus, false) => "__rust_u128_add",
-                    (BinaryOp::Minus, true) => "__rust_i128_sub",
-                    (BinaryOp::Minus, false) => "__rust_u128_sub",
+            if a_type != b_type {
+                b = self.gcc_int_cast(b, a_type);
+            }
+            // NOTE: the low halves are added as unsigned numbers in order to compute the carry
+            // (or the borrow) which is then propagated to the high halves.
+            let native_int_type = a_type.dyncast_array().expect("get element type");
+            let unsigned_type = native_int_type.to_unsigned(&self.cx);
+            let a_low = self.context.new_cast(None, self.low(a), unsigned_type);
+            let b_low = self.context.new_cast(None, self.low(b), unsigned_type);
+            let low = self.context.new_binary_op(None, operation, unsigned_type, a_low, b_low);
+            let carry =
+                match operation {
+                    BinaryOp::Plus => self.context.new_comparison(None, ComparisonOp::LessThan, low, a_low),
+                    BinaryOp::Minus => self.context.new_comparison(None, ComparisonOp::LessThan, a_low, b_low),
                     _ => unreachable!("unexpected additive operation {:?}", operation),
                 };
-            let param_a = self.context.new_parameter(None, a_type, "a");
-            let param_b = self.context.new_parameter(None, b_type, "b");
-            let func = self.context.new_function(None, FunctionType::Extern, a_type, &[param_a, param_b], func_name, false);
-            self.context.new_call(None, func, &[a, b])
+            let carry = self.context.new_cast(None, carry, native_int_type);
+            let high = self.context.new_binary_op(None, operation, native_int_type, self.high(a), self.high(b));
+            let high = self.context.new_binary_op(None, operation, native_int_type, high, carry);
+            self.from_low_high_rvalues(a_type, self.context.new_cast(None, low, native_int_type), high)
         }
     }
 
@@ -201,6 +198,8 @@ impl<'a, 'gcc, 'tcx> Builder<'a, 'gcc, 'tcx> {
             self.context.new_binary_op(None, operation, a_type, a, b)
         }
         else {
+            // NOTE: compiler-builtins provides these functions for all targets, unlike libgcc
+            // which only provides them for 64-bit targets.
             let sign =
                 if signed {
                     ""
@@ -295,41 +294,8 @@ impl<'a, 'gcc, 'tcx> Builder<'a, 'gcc, 'tcx> {
             }
             else {
                 match new_kind {
-                    Int(I128) | Uint(U128) => {
-                        let func_name =
-                            match oop {
-                                OverflowOp::Add =>
-                                    match new_kind {
-                                        Int(I128) => "__rust_i128_addo",
-                                        Uint(U128) => "__rust_u128_addo",
-                                        _ => unreachable!(),
-                                    },
-                                OverflowOp::Sub =>
-                                    match new_kind {
-                                        Int(I128) => "__rust_i128_subo",
-                                        Uint(U128) => "__rust_u128_subo",
-                                        _ => unreachable!(),
-                                    },
-                                OverflowOp::Mul =>
-                                    match new_kind {
-                                        Int(I128) => "__rust_i128_mulo", // TODO(antoyo): use __muloti4d instead?
-                                        Uint(U128) => "__rust_u128_mulo",
-                                        _ => unreachable!(),
-                                    },
-                            };
-                        let a_type = lhs.get_type();
-                        let b_type = rhs.get_type();
-                        let param_a = self.context.new_parameter(None, a_type, "a");
-                        let param_b = self.context.new_parameter(None, b_type, "b");
-                        let result_field = self.context.new_field(None, a_type, "result");
-                        let overflow_field = self.context.new_field(None, self.bool_type, "overflow");
-                        let return_type = self.context.new_struct_type(None, "result_overflow", &[result_field, overflow_field]);
-                        let func = self.context.new_function(None, FunctionType::Extern, return_type.as_type(), &[param_a, param_b], func_name, false);
-                        let result = self.context.new_call(None, func, &[lhs, rhs]);
-                        let overflow = result.access_field(None, overflow_field);
-                        let int_result = result.access_field(None, result_field);
-                        return (int_result, overflow);
-                    },
+                    Int(I128) => return self.non_native_checked_binop(oop, true, lhs, rhs),
+                    Uint(U128) => return self.non_native_checked_binop(oop, false, lhs, rhs),
                     _ => {
                         match oop {
                             OverflowOp::Mul =>
@@ -338,7 +304,10 @@ impl<'a, 'gcc, 'tcx> Builder<'a, 'gcc, 'tcx> {
                                     Int(I64) => "__mulodi4",
                                     _ => unreachable!(),
                                 },
-                            _ => unimplemented!("overflow operation for {:?}", new_kind),
+                            _ => {
+                                self.cx.report_unsupported(&format!("overflow-checked {:?} on {:?}", oop, new_kind));
+                                return (self.context.new_rvalue_zero(lhs.get_type()), self.context.new_rvalue_zero(self.bool_type));
+                            },
                         }
                     }
                 }
@@ -353,41 +322,124 @@ impl<'a, 'gcc, 'tcx> Builder<'a, 'gcc, 'tcx> {
         (res.dereference(None).to_rvalue(), overflow)
     }
 
+    /// Overflow-checked operation on non-native integers.
+    ///
+    /// NOTE: the __rust_*o functions of compiler-builtins are not used since they are not
+    /// guaranteed to use the C ABI.
+    pub fn non_native_checked_binop(&self, oop: OverflowOp, signed: bool, lhs: RValue<'gcc>, rhs: RValue<'gcc>) -> (RValue<'gcc>, RValue<'gcc>) {
+        let typ = lhs.get_type();
+        let native_int_type = typ.dyncast_array().expect("get element type");
+        let zero = self.gcc_zero(native_int_type);
+        match oop {
+            OverflowOp::Add | OverflowOp::Sub => {
+                let result = self.current_func().new_local(None, typ, "binopResult");
+                let operation_result =
+                    if let OverflowOp::Add = oop {
+                        self.gcc_add(lhs, rhs)
+                    }
+                    else {
+                        self.gcc_sub(lhs, rhs)
+                    };
+                self.llbb().add_assignment(None, result, operation_result);
+                let result = result.to_rvalue();
+                let overflow =
+                    match (oop, signed) {
+                        // The sign of the result differs from the sign of both operands.
+                        (OverflowOp::Add, true) => {
+                            let sign_bits = self.gcc_and(self.gcc_xor(lhs, result), self.gcc_xor(rhs, result));
+                            self.context.new_comparison(None, ComparisonOp::LessThan, self.high(sign_bits), zero)
+                        },
+                        // The operands have different signs and the sign of the result differs
+                        // from the sign of lhs.
+                        (OverflowOp::Sub, true) => {
+                            let sign_bits = self.gcc_and(self.gcc_xor(lhs, rhs), self.gcc_xor(lhs, result));
+                            self.context.new_comparison(None, ComparisonOp::LessThan, self.high(sign_bits), zero)
+                        },
+                        (OverflowOp::Add, false) => self.gcc_icmp(IntPredicate::IntULT, result, lhs),
+                        (OverflowOp::Sub, false) => self.gcc_icmp(IntPredicate::IntULT, lhs, rhs),
+                        (OverflowOp::Mul, _) => unreachable!(),
+                    };
+                (result, overflow)
+            },
+            OverflowOp::Mul if signed => {
+                // NOTE: compiler-builtins provides __muloti4 for all targets.
+                let overflow = self.current_func().new_local(None, self.int_type, "overflow");
+                let param_a = self.context.new_parameter(None, typ, "a");
+                let param_b = self.context.new_parameter(None, rhs.get_type(), "b");
+                let param_overflow = self.context.new_parameter(None, self.int_type.make_pointer(), "overflow");
+                let func = self.context.new_function(None, FunctionType::Extern, typ, &[param_a, param_b, param_overflow], "__muloti4", false);
+                let result = self.current_func().new_local(None, typ, "binopResult");
+                self.llbb().add_assignment(None, result, self.context.new_call(None, func, &[lhs, rhs, overflow.get_address(None)]));
+                let overflow = self.context.new_comparison(None, ComparisonOp::NotEquals, overflow.to_rvalue(), self.context.new_rvalue_zero(self.int_type));
+                (result.to_rvalue(), overflow)
+            },
+            OverflowOp::Mul => {
+                // NOTE: there's no unsigned equivalent to __muloti4, so the multiplication
+                // overflowed if dividing the result by rhs doesn't give lhs.
+                let result = self.current_func().new_local(None, typ, "binopResult");
+                self.llbb().add_assignment(None, result, self.gcc_mul(lhs, rhs));
+                let result = result.to_rvalue();
+                let rhs_is_not_zero = self.gcc_icmp(IntPredicate::IntNE, rhs, self.gcc_zero(typ));
+                // NOTE: the logical and doesn't evaluate the division when rhs is zero.
+                let quotient_differs = self.gcc_icmp(IntPredicate::IntNE, self.gcc_udiv(result, rhs), lhs);
+                let overflow = self.context.new_binary_op(None, BinaryOp::LogicalAnd, self.bool_type, rhs_is_not_zero, quotient_differs);
+                (result, overflow)
+            },
+        }
+    }
+
     pub fn gcc_icmp(&self, op: IntPredicate, mut lhs: RValue<'gcc>, mut rhs: RValue<'gcc>) -> RValue<'gcc> {
         let a_type = lhs.get_type();
         let b_type = rhs.get_type();
         if self.is_non_native_int_type(a_type) || self.is_non_native_int_type(b_type) {
-            let signed = a_type.is_compatible_with(self.i128_type);
-            let sign =
-                if signed {
-                    ""
+            // NOTE: __cmpti2 and __ucmpti2 are not available on 32-bit targets, so compare the
+            // halves: the high halves with the signedness of the predicate, then the low halves
+            // as unsigned numbers if the high halves are equal.
+            if a_type != b_type {
+                if self.is_non_native_int_type(a_type) {
+                    rhs = self.gcc_int_cast(rhs, a_type);
                 }
                 else {
-                    "u"
-                };
-            let func_name = format!("__{}cmpti2", sign);
-            let param_a = self.context.new_parameter(None, a_type, "a");
-            let param_b = self.context.new_parameter(None, b_type, "b");
-            let func = self.context.new_function(None, FunctionType::Extern, self.int_type, &[param_a, param_b], func_name, false);
-            let cmp = self.context.new_call(None, func, &[lhs, rhs]);
-            let (op, limit) =
+                    lhs = self.gcc_int_cast(lhs, b_type);
+                }
+            }
+            let native_int_type = lhs.get_type().dyncast_array().expect("get element type");
+            let high_equals = self.context.new_comparison(None, ComparisonOp::Equals, self.high(lhs), self.high(rhs));
+            let low_equals = self.context.new_comparison(None, ComparisonOp::Equals, self.low(lhs), self.low(rhs));
+            let (strict_op, op, signed) =
                 match op {
                     IntPredicate::IntEQ => {
-                        return self.context.new_comparison(None, ComparisonOp::Equals, cmp, self.context.new_rvalue_one(self.int_type));
+                        return self.context.new_binary_op(None, BinaryOp::LogicalAnd, self.bool_type, high_equals, low_equals);
                     },
                     IntPredicate::IntNE => {
-                        return self.context.new_comparison(None, ComparisonOp::NotEquals, cmp, self.context.new_rvalue_one(self.int_type));
+                        let equals = self.context.new_binary_op(None, BinaryOp::LogicalAnd, self.bool_type, high_equals, low_equals);
+                        return self.context.new_unary_op(None, UnaryOp::LogicalNegate, self.bool_type, equals);
                     },
-                    IntPredicate::IntUGT => (ComparisonOp::Equals, 2),
-                    IntPredicate::IntUGE => (ComparisonOp::GreaterThanEquals, 1),
-                    IntPredicate::IntULT => (ComparisonOp::Equals, 0),
-                    IntPredicate::IntULE => (ComparisonOp::LessThanEquals, 1),
-                    IntPredicate::IntSGT => (ComparisonOp::Equals, 2),
-                    IntPredicate::IntSGE => (ComparisonOp::GreaterThanEquals, 1),
-                    IntPredicate::IntSLT => (ComparisonOp::Equals, 0),
-                    IntPredicate::IntSLE => (ComparisonOp::LessThanEquals, 1),
+                    IntPredicate::IntUGT => (ComparisonOp::GreaterThan, ComparisonOp::GreaterThan, false),
+                    IntPredicate::IntUGE => (ComparisonOp::GreaterThan, ComparisonOp::GreaterThanEquals, false),
+                    IntPredicate::IntULT => (ComparisonOp::LessThan, ComparisonOp::LessThan, false),
+                    IntPredicate::IntULE => (ComparisonOp::LessThan, ComparisonOp::LessThanEquals, false),
+                    IntPredicate::IntSGT => (ComparisonOp::GreaterThan, ComparisonOp::GreaterThan, true),
+                    IntPredicate::IntSGE => (ComparisonOp::GreaterThan, ComparisonOp::GreaterThanEquals, true),
+                    IntPredicate::IntSLT => (ComparisonOp::LessThan, ComparisonOp::LessThan, true),
+                    IntPredicate::IntSLE => (ComparisonOp::LessThan, ComparisonOp::LessThanEquals, true),
                 };
-            self.context.new_comparison(None, op, cmp, self.context.new_rvalue_from_int(self.int_type, limit))
+            let high_type =
+                if signed {
+                    native_int_type.to_signed(&self.cx)
+                }
+                else {
+                    native_int_type.to_unsigned(&self.cx)
+                };
+            let unsigned_type = native_int_type.to_unsigned(&self.cx);
+            let lhs_high = self.context.new_cast(None, self.high(lhs), high_type);
+            let rhs_high = self.context.new_cast(None, self.high(rhs), high_type);
+            let lhs_low = self.context.new_cast(None, self.low(lhs), unsigned_type);
+            let rhs_low = self.context.new_cast(None, self.low(rhs), unsigned_type);
+            let high_comparison = self.context.new_comparison(None, strict_op, lhs_high, rhs_high);
+            let low_comparison = self.context.new_comparison(None, op, lhs_low, rhs_low);
+            let low_comparison = self.context.new_binary_op(None, BinaryOp::LogicalAnd, self.bool_type, high_equals, low_comparison);
+            self.context.new_binary_op(None, BinaryOp::LogicalOr, self.bool_type, high_comparison, low_comparison)
         }
         else if a_type.get_pointee().is_some() && b_type.get_pointee().is_some() {
             // NOTE: gcc cannot compare pointers to different objects, but rustc does that, so cast them to usize.
@@ -418,11 +470,7 @@ impl<'a, 'gcc, 'tcx> Builder<'a, 'gcc, 'tcx> {
             a ^ b
         }
         else {
-            let values = [
-                self.low(a) ^ self.low(b),
-                self.high(a) ^ self.high(b),
-            ];
-            self.context.new_array_constructor(None, a_type, &values)
+            self.from_low_high_rvalues(a_type, self.low(a) ^ self.low(b), self.high(a) ^ self.high(b))
         }
     }
 
@@ -463,17 +511,12 @@ impl<'a, 'gcc, 'tcx> Builder<'a, 'gcc, 'tcx> {
             let result = func.new_local(None, a_type, "shiftResult");
 
             let b = self.gcc_int_cast(b, native_int_type);
-            let sixty_four = self.gcc_int(native_int_type, 64);
+            let native_int_width = self.gcc_int(native_int_type, self.native_int_width(native_int_type));
             let zero = self.gcc_zero(native_int_type);
-            let condition = self.gcc_icmp(IntPredicate::IntNE, self.gcc_and(b, sixty_four), zero);
+            let condition = self.gcc_icmp(IntPredicate::IntNE, self.gcc_and(b, native_int_width), zero);
             self.llbb().end_with_conditional(None, condition, then_block, else_block);
 
-            // TODO(antoyo): take endianness into account.
-            let values = [
-                zero,
-                self.low(a) << (b - sixty_four),
-            ];
-            let array_value = self.context.new_array_constructor(None, a_type, &values);
+            let array_value = self.from_low_high_rvalues(a_type, zero, self.low(a) << (b - native_int_width));
             then_block.add_assignment(None, result, array_value);
             then_block.end_with_jump(None, after_block);
 
@@ -486,14 +529,9 @@ impl<'a, 'gcc, 'tcx> Builder<'a, 'gcc, 'tcx> {
             // NOTE: cast low to its unsigned type in order to perform a logical right shift.
             let unsigned_type = native_int_type.to_unsigned(&self.cx);
             let casted_low = self.context.new_cast(None, self.low(a), unsigned_type);
-            let shift_value = self.context.new_cast(None, sixty_four - b, unsigned_type);
+            let shift_value = self.context.new_cast(None, native_int_width - b, unsigned_type);
             let high_low = self.context.new_cast(None, casted_low >> shift_value, native_int_type);
-            let values = [
-                self.low(a) << b,
-                (self.high(a) << b) | high_low,
-            ];
-
-            let array_value = self.context.new_array_constructor(None, a_type, &values);
+            let array_value = self.from_low_high_rvalues(a_type, self.low(a) << b, (self.high(a) << b) | high_low);
             actual_else_block.add_assignment(None, result, array_value);
             actual_else_block.end_with_jump(None, after_block);
 
@@ -509,16 +547,16 @@ impl<'a, 'gcc, 'tcx> Builder<'a, 'gcc, 'tcx> {
         let arg_type = arg.get_type();
         if !self.is_native_int_type(arg_type) {
             let native_int_type = arg_type.dyncast_array().expect("get element type");
-            let lsb = self.context.new_array_access(None, arg, self.context.new_rvalue_from_int(self.int_type, 0)).to_rvalue();
+            let lsb = self.low(arg);
             let swapped_lsb = self.gcc_bswap(lsb, width / 2);
             let swapped_lsb = self.context.new_cast(None, swapped_lsb, native_int_type);
-            let msb = self.context.new_array_access(None, arg, self.context.new_rvalue_from_int(self.int_type, 1)).to_rvalue();
+            let msb = self.high(arg);
             let swapped_msb = self.gcc_bswap(msb, width / 2);
             let swapped_msb = self.context.new_cast(None, swapped_msb, native_int_type);
 
             // NOTE: we also need to swap the two elements here, in addition to swapping inside
             // the elements themselves like done above.
-            return self.context.new_array_constructor(None, arg_type, &[swapped_msb, swapped_lsb]);
+            return self.from_low_high_rvalues(arg_type, swapped_msb, swapped_lsb);
         }
 
         // TODO(antoyo): check if it's faster to use string literals and a
@@ -589,6 +627,11 @@ impl<'gcc, 'tcx> CodegenCx<'gcc, 'tcx> {
         }
     }
 
+    /// Width of the halves of a non-native integer.
+    fn native_int_width(&self, native_int_type: Type<'gcc>) -> i64 {
+        native_int_type.get_size() as i64 * 8
+    }
+
     pub fn gcc_int_width(&self, typ: Type<'gcc>) -> u64 {
         if self.is_native_int_type_or_bool(typ) {
             typ.get_size() as u64 * 8
@@ -616,11 +659,10 @@ impl<'gcc, 'tcx> CodegenCx<'gcc, 'tcx> {
         else {
             assert!(!a_native && !b_native, "both types should either be native or non-native for or operation");
             let native_int_type = a_type.dyncast_array().expect("get element type");
-            let values = [
+            self.from_low_high_rvalues(a_type,
                 self.context.new_binary_op(None, operation, native_int_type, self.low(a), self.low(b)),
                 self.context.new_binary_op(None, operation, native_int_type, self.high(a), self.high(b)),
-            ];
-            self.context.new_array_constructor(None, a_type, &values)
+            )
         }
     }
 
@@ -644,11 +686,10 @@ impl<'gcc, 'tcx> CodegenCx<'gcc, 'tcx> {
             let zero = self.context.new_rvalue_zero(value_type);
             let is_negative = self.context.new_comparison(None, ComparisonOp::LessThan, value, zero);
             let is_negative = self.gcc_int_cast(is_negative, dest_element_type);
-            let values = [
+            self.from_low_high_rvalues(dest_typ,
                 self.context.new_cast(None, value, dest_element_type),
                 self.context.new_unary_op(None, UnaryOp::Minus, dest_element_type, is_negative),
-            ];
-            self.context.new_array_constructor(None, dest_typ, &values)
+            )
         }
         else {
             // Since u128 and i128 are the only types that can be unsupported, we know the type of
@@ -678,6 +719,7 @@ impl<'gcc, 'tcx> CodegenCx<'gcc, 'tcx> {
             else {
                 "un"
             };
+        // NOTE: compiler-builtins provides these functions for all targets.
         let func_name = format!("__float{}{}", sign, name_suffix);
         let param = self.context.new_parameter(None, value_type, "n");
         let func = self.context.new_function(None, FunctionType::Extern, dest_typ, &[param], func_name, false);
@@ -711,6 +753,7 @@ impl<'gcc, 'tcx> CodegenCx<'gcc, 'tcx> {
             else {
                 "uns"
             };
+        // NOTE: compiler-builtins provides these functions for all targets.
         let func_name = format!("__fix{}{}", sign, name_suffix);
         let param = self.context.new_parameter(None, value_type, "n");
         let func = self.context.new_function(None, FunctionType::Extern, dest_typ, &[param], func_name, false);
@@ -725,22 +768,43 @@ impl<'gcc, 'tcx> CodegenCx<'gcc, 'tcx> {
         self.float_to_int_cast(false, value, dest_typ)
     }
 
+    /// Index of the most significant half of a non-native integer.
+    ///
+    /// NOTE: the halves are stored in the order of the target so that non-native integers have the
+    /// same layout in memory as native integers.
+    fn high_index(&self) -> i32 {
+        match self.tcx.data_layout.endian {
+            Endian::Little => 1,
+            Endian::Big => 0,
+        }
+    }
+
     fn high(&self, value: RValue<'gcc>) -> RValue<'gcc> {
-        self.context.new_array_access(None, value, self.context.new_rvalue_from_int(self.int_type, 1))
+        let index = self.context.new_rvalue_from_int(self.int_type, self.high_index());
+        self.context.new_array_access(None, value, index)
             .to_rvalue()
     }
 
     fn low(&self, value: RValue<'gcc>) -> RValue<'gcc> {
-        self.context.new_array_access(None, value, self.context.new_rvalue_from_int(self.int_type, 0))
+        let index = self.context.new_rvalue_from_int(self.int_type, 1 - self.high_index());
+        self.context.new_array_access(None, value, index)
             .to_rvalue()
     }
 
+    fn from_low_high_rvalues(&self, typ: Type<'gcc>, low: RValue<'gcc>, high: RValue<'gcc>) -> RValue<'gcc> {
+        let values =
+            match self.tcx.data_layout.endian {
+                Endian::Little => [low, high],
+                Endian::Big => [high, low],
+            };
+        self.context.new_array_constructor(None, typ, &values)
+    }
+
     fn from_low_high(&self, typ: Type<'gcc>, low: i64, high: i64) -> RValue<'gcc> {
         let native_int_type = typ.dyncast_array().expect("get element type");
-        let values = [
+        self.from_low_high_rvalues(typ,
             self.context.new_rvalue_from_long(native_int_type, low),
             self.context.new_rvalue_from_long(native_int_type, high),
-        ];
-        self.context.new_array_constructor(None, typ, &values)
+        )
     }
 }
@@ -420,10 +420,20 @@ pub fn intrinsic<'gcc, 'tcx>(name: &str, cx: &CodegenCx<'gcc, 'tcx>) -> Function
             cx.functions.borrow_mut().insert(gcc_name.to_string(), func);
             return func;
         },
-        _ => unimplemented!("unsupported LLVM intrinsic {}", name),
+        _ => unsupported_intrinsic(name, cx),
     }
 }
 
+/// Reports an LLVM intrinsic that has no GCC equivalent and returns a dummy function so that the
+/// codegen can continue.
+pub fn unsupported_intrinsic<'gcc, 'tcx>(name: &str, cx: &CodegenCx<'gcc, 'tcx>) -> Function<'gcc> {
+    cx.report_unsupported(&format!("the LLVM intrinsic `{}`", name));
+    let gcc_name = "__builtin_trap";
+    let func = cx.context.get_builtin_function(gcc_name);
+    cx.functions.borrow_mut().insert(gcc_name.to_string(), func);
+    func
+}
+
 #[cfg(feature="master")]
 pub fn intrinsic<'gcc, 'tcx>(name: &str, cx: &CodegenCx<'gcc, 'tcx>) -> Function<'gcc> {
     match name {
@@ -10,7 +10,7 @@ use rustc_codegen_ssa::base::wants_msvc_seh;
 use rustc_codegen_ssa::common::IntPredicate;
 use rustc_codegen_ssa::mir::operand::{OperandRef, OperandValue};
 use rustc_codegen_ssa::mir::place::PlaceRef;
-use rustc_codegen_ssa::traits::{ArgAbiMethods, BaseTypeMethods, BuilderMethods, ConstMethods, IntrinsicCallMethods};
+use rustc_codegen_ssa::traits::{ArgAbiMethods, BaseTypeMethods, BuilderMethods, ConstMethods, IntrinsicCallMethods, OverflowOp};
 #[cfg(feature="master")]
 use rustc_codegen_ssa::traits::{DerivedTypeMethods, MiscMethods};
 use rustc_middle::bug;
@@ -132,13 +132,16 @@ impl<'a, 'gcc, 'tcx> IntrinsicCallMethods<'tcx> for Builder<'a, 'gcc, 'tcx> {
                     return;
                 }
                 sym::breakpoint => {
-                    unimplemented!();
+                    self.cx.report_unsupported("the `breakpoint` intrinsic");
+                    return;
                 }
                 sym::va_copy => {
-                    unimplemented!();
+                    self.cx.report_unsupported("the `va_copy` intrinsic");
+                    return;
                 }
                 sym::va_arg => {
-                    unimplemented!();
+                    self.cx.report_unsupported("the `va_arg` intrinsic");
+                    return;
                 }
 
                 sym::volatile_load | sym::unaligned_volatile_load => {
@@ -165,7 +168,8 @@ impl<'a, 'gcc, 'tcx> IntrinsicCallMethods<'tcx> for Builder<'a, 'gcc, 'tcx> {
                     | sym::prefetch_write_data
                     | sym::prefetch_read_instruction
                     | sym::prefetch_write_instruction => {
-                        unimplemented!();
+                        self.cx.report_unsupported(&format!("the `{}` intrinsic", name));
+                        return;
                     }
                 sym::ctlz
                     | sym::ctlz_nonzero
@@ -385,11 +389,13 @@ impl<'a, 'gcc, 'tcx> IntrinsicCallMethods<'tcx> for Builder<'a, 'gcc, 'tcx> {
     }
 
     fn va_start(&mut self, _va_list: RValue<'gcc>) -> RValue<'gcc> {
-        unimplemented!();
+        self.cx.report_unsupported("C-variadic functions (`va_start`)");
+        self.context.new_rvalue_from_int(self.int_type, 0)
     }
 
     fn va_end(&mut self, _va_list: RValue<'gcc>) -> RValue<'gcc> {
-        unimplemented!();
+        self.cx.report_unsupported("C-variadic functions (`va_end`)");
+        self.context.new_rvalue_from_int(self.int_type, 0)
     }
 }
 
@@ -927,20 +933,7 @@ impl<'a, 'gcc, 'tcx> Builder<'a, 'gcc, 'tcx> {
                     self.overflow_call(overflow_func, &[lhs, rhs, res.get_address(None)], None)
                 }
                 else {
-                    let func_name =
-                        match width {
-                            128 => "__rust_i128_addo",
-                            _ => unreachable!(),
-                        };
-                    let param_a = self.context.new_parameter(None, result_type, "a");
-                    let param_b = self.context.new_parameter(None, result_type, "b");
-                    let result_field = self.context.new_field(None, result_type, "result");
-                    let overflow_field = self.context.new_field(None, self.bool_type, "overflow");
-                    let return_type = self.context.new_struct_type(None, "result_overflow", &[result_field, overflow_field]);
-                    let func = self.context.new_function(None, FunctionType::Extern, return_type.as_type(), &[param_a, param_b], func_name, false);
-                    let result = self.context.new_call(None, func, &[lhs, rhs]);
-                    let overflow = result.access_field(None, overflow_field);
-                    let int_result = result.access_field(None, result_field);
+                    let (int_result, overflow) = self.non_native_checked_binop(OverflowOp::Add, true, lhs, rhs);
                     self.llbb().add_assignment(None, res, int_result);
                     overflow
                 };
@@ -997,20 +990,7 @@ impl<'a, 'gcc, 'tcx> Builder<'a, 'gcc, 'tcx> {
                     self.overflow_call(overflow_func, &[lhs, rhs, res.get_address(None)], None)
                 }
                 else {
-                    let func_name =
-                        match width {
-                            128 => "__rust_i128_subo",
-                            _ => unreachable!(),
-                        };
-                    let param_a = self.context.new_parameter(None, result_type, "a");
-                    let param_b = self.context.new_parameter(None, result_type, "b");
-                    let result_field = self.context.new_field(None, result_type, "result");
-                    let overflow_field = self.context.new_field(None, self.bool_type, "overflow");
-                    let return_type = self.context.new_struct_type(None, "result_overflow", &[result_field, overflow_field]);
-                    let func = self.context.new_function(None, FunctionType::Extern, return_type.as_type(), &[param_a, param_b], func_name, false);
-                    let result = self.context.new_call(None, func, &[lhs, rhs]);
-                    let overflow = result.access_field(None, overflow_field);
-                    let int_result = result.access_field(None, result_field);
+                    let (int_result, overflow) = self.non_native_checked_binop(OverflowOp::Sub, true, lhs, rhs);
                     self.llbb().add_assignment(None, res, int_result);
                     overflow
                 };
@@ -1053,13 +1033,13 @@ fn try_intrinsic<'a, 'b, 'gcc, 'tcx>(bx: &'b mut Builder<'a, 'gcc, 'tcx>, try_fu
         bx.store(bx.const_i32(0), dest, ret_align);
     }
     else if wants_msvc_seh(bx.sess()) {
-        unimplemented!();
+        bx.cx.report_unsupported("SEH-based unwinding (`catch_unwind`)");
     }
     else {
         #[cfg(feature="master")]
         codegen_gnu_try(bx, try_func, data, _catch_func, dest);
         #[cfg(not(feature="master"))]
-        unimplemented!();
+        bx.cx.report_unsupported("unwinding with this version of libgccjit (`catch_unwind`)");
     }
 }
 
@@ -1057,5 +1057,6 @@ pub fn generic_simd_intrinsic<'a, 'gcc, 'tcx>(
     bitwise_red!(simd_reduce_all: BinaryOp::BitwiseAnd, true);
     bitwise_red!(simd_reduce_any: BinaryOp::BitwiseOr, true);
 
-    unimplemented!("simd {}", name);
+    bx.cx.report_unsupported(&format!("the `{}` SIMD intrinsic", name));
+    Err(())
 }
@@ -59,16 +59,18 @@ mod errors;
 mod int;
 mod intrinsic;
 mod mono_item;
+mod options;
 mod type_;
 mod type_of;
 
 use std::any::Any;
 use std::sync::Arc;
 
-use crate::errors::LTONotSupported;
+use crate::errors::{LTONotSupported, NativeCpuNotSupported, OptionMissingValue, OptionUnexpectedValue, UnknownOption, UnsupportedRelocModel};
+use crate::options::{GccOptions, OptionError};
 use gccjit::{Context, OptimizationLevel, TargetInfo};
 use rustc_ast::expand::allocator::AllocatorKind;
-use rustc_codegen_ssa::{CodegenResults, CompiledModule, ModuleCodegen};
+use rustc_codegen_ssa::{CodegenResults, CompiledModule, ModuleCodegen, NativeLib};
 use rustc_codegen_ssa::base::codegen_crate;
 use rustc_codegen_ssa::back::write::{CodegenContext, FatLTOInput, ModuleConfig, TargetMachineFactoryFn};
 use rustc_codegen_ssa::back::lto::{LtoModuleCodegen, SerializedModule, ThinModule};
@@ -83,8 +85,10 @@ use rustc_middle::query::Providers;
 use rustc_middle::ty::TyCtxt;
 use rustc_session::config::{Lto, OptLevel, OutputFilenames};
 use rustc_session::Session;
+use rustc_session::utils::NativeLibKind;
 use rustc_span::Symbol;
 use rustc_span::fatal_error::FatalError;
+use rustc_target::spec::RelocModel;
 
 fluent_messages! { "../messages.ftl" }
 
@@ -109,11 +113,43 @@ impl CodegenBackend for GccCodegenBackend {
     }
 
     fn init(&self, sess: &Session) {
+        // NOTE: with -Cpanic=abort, nothing unwinds so the personality function is not needed.
         #[cfg(feature="master")]
-        gccjit::set_global_personality_function_name(b"rust_eh_personality\0");
+        if sess.panic_strategy() == rustc_target::spec::PanicStrategy::Unwind {
+            gccjit::set_global_personality_function_name(b"rust_eh_personality\0");
+        }
         if sess.lto() != Lto::No {
             sess.emit_warning(LTONotSupported {});
         }
+
+        // NOTE: the GCC driver replaces native by the name of the host CPU, but libgccjit and the
+        // target attribute don't.
+        if sess.opts.cg.target_cpu.as_deref() == Some("native") {
+            sess.emit_warning(NativeCpuNotSupported { option: "-Ctarget-cpu" });
+        }
+        if sess.opts.unstable_opts.tune_cpu.as_deref() == Some("native") {
+            sess.emit_warning(NativeCpuNotSupported { option: "-Ztune-cpu" });
+        }
+
+        let reloc_model =
+            match sess.relocation_model() {
+                RelocModel::Ropi => Some("ropi"),
+                RelocModel::Rwpi => Some("rwpi"),
+                RelocModel::RopiRwpi => Some("ropi-rwpi"),
+                _ => None,
+            };
+        if let Some(reloc_model) = reloc_model {
+            sess.emit_err(UnsupportedRelocModel { reloc_model });
+        }
+
+        let (_, errors) = GccOptions::parse(&sess.opts);
+        for error in errors {
+            match error {
+                OptionError::Unknown(option) => sess.emit_err(UnknownOption { option: &option }),
+                OptionError::MissingValue(option) => sess.emit_err(OptionMissingValue { option: &option }),
+                OptionError::UnexpectedValue(option) => sess.emit_err(OptionUnexpectedValue { option: &option }),
+            };
+        }
     }
 
     fn provide(&self, providers: &mut Providers) {
@@ -137,9 +173,21 @@ impl CodegenBackend for GccCodegenBackend {
         Ok((codegen_results, work_products))
     }
 
-    fn link(&self, sess: &Session, codegen_results: CodegenResults, outputs: &OutputFilenames) -> Result<(), ErrorGuaranteed> {
+    fn link(&self, sess: &Session, mut codegen_results: CodegenResults, outputs: &OutputFilenames) -> Result<(), ErrorGuaranteed> {
         use rustc_codegen_ssa::back::link::link_binary;
 
+        // NOTE: the code instrumented by -Zprofile calls the functions of libgcov.
+        if sess.opts.unstable_opts.profile {
+            codegen_results.crate_info.used_libraries.push(NativeLib {
+                kind: NativeLibKind::Unspecified,
+                name: Symbol::intern("gcov"),
+                filename: None,
+                cfg: None,
+                verbatim: false,
+                dll_imports: vec![],
+            });
+        }
+
         link_binary(
             sess,
             &crate::archive::ArArchiveBuilderBuilder,
@@ -157,6 +205,8 @@ impl ExtraBackendMethods for GccCodegenBackend {
     fn codegen_allocator<'tcx>(&self, tcx: TyCtxt<'tcx>, module_name: &str, kind: AllocatorKind, alloc_error_handler_kind: AllocatorKind) -> Self::Module {
         let mut mods = GccContext {
             context: Context::default(),
+            error_reported: false,
+            libgccjit_error: false,
         };
         unsafe { allocator::codegen(tcx, &mut mods, module_name, kind, alloc_error_handler_kind); }
         mods
@@ -192,10 +242,18 @@ impl ThinBufferMethods for ThinBuffer {
 
 pub struct GccContext {
     context: Context<'static>,
+    /// Whether an error (from libgccjit or an unsupported feature) was already reported while
+    /// codegenning this module.
+    error_reported: bool,
+    /// Whether the error reported while codegenning this module came from libgccjit, in which case
+    /// a reproducer is dumped when the module is compiled.
+    libgccjit_error: bool,
 }
 
 unsafe impl Send for GccContext {}
-// FIXME(antoyo): that shouldn't be Sync. Parallel compilation is currently disabled with "-Zno-parallel-llvm". Try to disable it here.
+// NOTE: Sync is required by WriteBackendMethods::Module, but a context is never used by two threads
+// at the same time: each module is owned by a single worker and the parallel compilation happens in
+// forked processes (see back::write::compile_to_file).
 unsafe impl Sync for GccContext {}
 
 impl WriteBackendMethods for GccCodegenBackend {
@@ -225,7 +283,7 @@ impl WriteBackendMethods for GccCodegenBackend {
     }
 
     fn print_pass_timings(&self) {
-        unimplemented!();
+        back::timings::print_time_reports();
     }
 
     unsafe fn optimize(_cgcx: &CodegenContext<Self>, _diag_handler: &Handler, module: &ModuleCodegen<Self::Module>, config: &ModuleConfig) -> Result<(), FatalError> {
@@ -291,21 +349,17 @@ fn to_gcc_opt_level(optlevel: Option<OptLevel>) -> OptimizationLevel {
     }
 }
 
-fn handle_native(name: &str) -> &str {
-    if name != "native" {
-        return name;
-    }
-
-    unimplemented!();
-}
-
 pub fn target_cpu(sess: &Session) -> &str {
     match sess.opts.cg.target_cpu {
-        Some(ref name) => handle_native(name),
-        None => handle_native(sess.target.cpu.as_ref()),
+        Some(ref name) => name,
+        None => sess.target.cpu.as_ref(),
     }
 }
 
+pub fn tune_cpu(sess: &Session) -> Option<&str> {
+    sess.opts.unstable_opts.tune_cpu.as_deref()
+}
+
 pub fn target_features(sess: &Session, allow_unstable: bool, target_info: &Arc<TargetInfo>) -> Vec<Symbol> {
     supported_target_features(sess)
         .iter()
@@ -0,0 +1,388 @@
+//! Options of the GCC backend.
+//!
+//! They can be set with `-Cllvm-args=-gcc-<name>[=<value>]` or with the `CG_GCCJIT_*` environment
+//! variables, the command-line arguments being applied after the environment variables.
+//! The other `-Cllvm-args` are passed to GCC as compiler options.
+
+use std::env;
+use std::path::PathBuf;
+
+use rustc_session::config::{Options, OutputFilenames};
+
+const OPTION_PREFIX: &str = "-gcc-";
+
+/// Set of modules an option applies to.
+#[derive(Clone, Debug, Default)]
+pub enum ModuleFilter {
+    #[default]
+    Nothing,
+    All,
+    Modules(Vec<String>),
+}
+
+impl ModuleFilter {
+    pub fn matches(&self, module_name: &str) -> bool {
+        match *self {
+            ModuleFilter::Nothing => false,
+            ModuleFilter::All => true,
+            ModuleFilter::Modules(ref modules) => modules.iter().any(|module| module == module_name),
+        }
+    }
+
+    /// Adds the modules from a comma-separated list. No list means all the modules.
+    fn add(&mut self, modules: Option<&str>) {
+        let modules =
+            match modules {
+                Some(modules) => modules,
+                None => {
+                    *self = ModuleFilter::All;
+                    return;
+                },
+            };
+        let modules = modules.split(',')
+            .filter(|module| !module.is_empty())
+            .map(|module| module.to_string());
+        match *self {
+            ModuleFilter::Nothing => *self = ModuleFilter::Modules(modules.collect()),
+            ModuleFilter::All => (),
+            ModuleFilter::Modules(ref mut current_modules) => current_modules.extend(modules),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct GccOptions {
+    /// Options passed to the GCC compiler.
+    pub command_line_options: Vec<String>,
+    /// Options passed to the GCC driver (e.g. linker or assembler options).
+    pub driver_options: Vec<String>,
+    /// Print the name of the modules before compiling them.
+    pub dump_module_names: bool,
+    /// Modules for which a reproducer is dumped even if they compile successfully.
+    pub dump_reproducer: ModuleFilter,
+    /// Directory where the reproducers are written.
+    pub reproducer_dir: Option<PathBuf>,
+    /// Modules dumped as pseudo-C code, with debug info pointing to this dump.
+    pub dump_to_file: ModuleFilter,
+    /// Directory where the pseudo-C code of the modules is written.
+    pub dump_dir: Option<PathBuf>,
+    pub dump_rtl: ModuleFilter,
+    pub dump_tree_all: ModuleFilter,
+    pub dump_code: ModuleFilter,
+    pub dump_gimple: ModuleFilter,
+    pub dump_everything: ModuleFilter,
+    pub keep_intermediates: ModuleFilter,
+    /// Call `__cyg_profile_func_enter` and `__cyg_profile_func_exit` in every function.
+    pub instrument_functions: bool,
+    /// Paths of the functions (or of their modules) that are not instrumented.
+    pub instrument_functions_exclude: Vec<String>,
+    /// Emulate the 128-bit integers even if the target supports them.
+    pub emulate_128bit_integers: bool,
+}
+
+/// Error found while parsing the options. The option is formatted as it was written by the user.
+#[derive(Debug, PartialEq)]
+pub enum OptionError {
+    Unknown(String),
+    MissingValue(String),
+    UnexpectedValue(String),
+}
+
+#[derive(Clone, Copy)]
+enum OptionKind {
+    /// The option doesn't take a value.
+    Flag,
+    /// The option takes an optional list of modules.
+    Modules,
+    /// The option requires a value.
+    Value,
+}
+
+/// Environment variable setting an option.
+#[derive(Clone, Copy)]
+enum EnvVar {
+    /// The variable enables the option, without a value, when it is `1`.
+    Enable(&'static str),
+    /// The variable gives the value of the option.
+    Value(&'static str),
+}
+
+struct OptionDesc {
+    name: &'static str,
+    env_vars: &'static [EnvVar],
+    kind: OptionKind,
+    apply: fn(&mut GccOptions, Option<&str>),
+}
+
+const OPTIONS: &[OptionDesc] = &[
+    OptionDesc {
+        name: "option",
+        env_vars: &[],
+        kind: OptionKind::Value,
+        apply: |options, value| options.command_line_options.push(value.unwrap_or_default().to_string()),
+    },
+    OptionDesc {
+        name: "driver-option",
+        env_vars: &[],
+        kind: OptionKind::Value,
+        apply: |options, value| options.driver_options.push(value.unwrap_or_default().to_string()),
+    },
+    OptionDesc {
+        name: "dump-module-names",
+        env_vars: &[EnvVar::Enable("CG_GCCJIT_DUMP_MODULE_NAMES")],
+        kind: OptionKind::Flag,
+        apply: |options, _| options.dump_module_names = true,
+    },
+    OptionDesc {
+        name: "dump-reproducer",
+        env_vars: &[EnvVar::Enable("CG_GCCJIT_DUMP_ALL_MODULES"), EnvVar::Value("CG_GCCJIT_DUMP_MODULE")],
+        kind: OptionKind::Modules,
+        apply: |options, modules| options.dump_reproducer.add(modules),
+    },
+    OptionDesc {
+        name: "reproducer-dir",
+        env_vars: &[EnvVar::Value("CG_GCCJIT_REPRODUCER_DIR")],
+        kind: OptionKind::Value,
+        apply: |options, dir| options.reproducer_dir = dir.map(PathBuf::from),
+    },
+    OptionDesc {
+        name: "dump-to-file",
+        env_vars: &[EnvVar::Enable("CG_GCCJIT_DUMP_TO_FILE")],
+        kind: OptionKind::Modules,
+        apply: |options, modules| options.dump_to_file.add(modules),
+    },
+    OptionDesc {
+        name: "dump-dir",
+        env_vars: &[EnvVar::Value("CG_GCCJIT_DUMP_DIR")],
+        kind: OptionKind::Value,
+        apply: |options, dir| options.dump_dir = dir.map(PathBuf::from),
+    },
+    OptionDesc {
+        name: "dump-rtl",
+        env_vars: &[EnvVar::Enable("CG_GCCJIT_DUMP_RTL")],
+        kind: OptionKind::Modules,
+        apply: |options, modules| options.dump_rtl.add(modules),
+    },
+    OptionDesc {
+        name: "dump-tree-all",
+        env_vars: &[EnvVar::Enable("CG_GCCJIT_DUMP_TREE_ALL")],
+        kind: OptionKind::Modules,
+        apply: |options, modules| options.dump_tree_all.add(modules),
+    },
+    OptionDesc {
+        name: "dump-code",
+        env_vars: &[EnvVar::Enable("CG_GCCJIT_DUMP_CODE")],
+        kind: OptionKind::Modules,
+        apply: |options, modules| options.dump_code.add(modules),
+    },
+    OptionDesc {
+        name: "dump-gimple",
+        env_vars: &[EnvVar::Enable("CG_GCCJIT_DUMP_GIMPLE")],
+        kind: OptionKind::Modules,
+        apply: |options, modules| options.dump_gimple.add(modules),
+    },
+    OptionDesc {
+        name: "dump-everything",
+        env_vars: &[EnvVar::Enable("CG_GCCJIT_DUMP_EVERYTHING")],
+        kind: OptionKind::Modules,
+        apply: |options, modules| options.dump_everything.add(modules),
+    },
+    OptionDesc {
+        name: "keep-intermediates",
+        env_vars: &[EnvVar::Enable("CG_GCCJIT_KEEP_INTERMEDIATES")],
+        kind: OptionKind::Modules,
+        apply: |options, modules| options.keep_intermediates.add(modules),
+    },
+    OptionDesc {
+        name: "instrument-functions",
+        env_vars: &[EnvVar::Enable("CG_GCCJIT_INSTRUMENT_FUNCTIONS")],
+        kind: OptionKind::Flag,
+        apply: |options, _| options.instrument_functions = true,
+    },
+    OptionDesc {
+        name: "instrument-functions-exclude",
+        env_vars: &[EnvVar::Value("CG_GCCJIT_INSTRUMENT_FUNCTIONS_EXCLUDE")],
+        kind: OptionKind::Value,
+        apply: |options, paths| {
+            let paths = paths.unwrap_or_default().split(',')
+                .filter(|path| !path.is_empty())
+                .map(|path| path.to_string());
+            options.instrument_functions_exclude.extend(paths);
+        },
+    },
+    OptionDesc {
+        name: "emulate-128bit-integers",
+        env_vars: &[EnvVar::Enable("CG_GCCJIT_EMULATE_128BIT_INTEGERS")],
+        kind: OptionKind::Flag,
+        apply: |options, _| options.emulate_128bit_integers = true,
+    },
+];
+
+impl GccOptions {
+    /// Parses the options, ignoring the invalid ones.
+    ///
+    /// NOTE: the options are parsed for every module because `WriteBackendMethods` doesn't have
+    /// access to the backend. The errors are reported once by `CodegenBackend::init`.
+    pub fn new(opts: &Options) -> Self {
+        GccOptions::parse(opts).0
+    }
+
+    pub fn parse(opts: &Options) -> (Self, Vec<OptionError>) {
+        GccOptions::parse_from(&opts.cg.llvm_args, |name| env::var_os(name).map(|value| value.to_string_lossy().into_owned()))
+    }
+
+    /// Parses the options from `llvm_args` and from the environment variables given by `env_var`.
+    fn parse_from(llvm_args: &[String], env_var: impl Fn(&str) -> Option<String>) -> (Self, Vec<OptionError>) {
+        let mut options = GccOptions::default();
+        let mut errors = vec![];
+
+        for desc in OPTIONS {
+            for &var in desc.env_vars {
+                match var {
+                    EnvVar::Enable(name) =>
+                        if env_var(name).as_deref() == Some("1") {
+                            (desc.apply)(&mut options, None);
+                        },
+                    EnvVar::Value(name) =>
+                        if let Some(value) = env_var(name).filter(|value| !value.is_empty()) {
+                            (desc.apply)(&mut options, Some(&value));
+                        },
+                }
+            }
+        }
+
+        for arg in llvm_args {
+            let option =
+                match arg.strip_prefix(OPTION_PREFIX) {
+                    Some(option) => option,
+                    None => {
+                        options.command_line_options.push(arg.clone());
+                        continue;
+                    },
+                };
+            let (name, value) =
+                match option.split_once('=') {
+                    Some((name, value)) => (name, Some(value)),
+                    None => (option, None),
+                };
+            let desc =
+                match OPTIONS.iter().find(|desc| desc.name == name) {
+                    Some(desc) => desc,
+                    None => {
+                        errors.push(OptionError::Unknown(arg.clone()));
+                        continue;
+                    },
+                };
+            match (desc.kind, value) {
+                (OptionKind::Flag, Some(_)) => errors.push(OptionError::UnexpectedValue(arg.clone())),
+                (OptionKind::Value, None | Some("")) => errors.push(OptionError::MissingValue(arg.clone())),
+                _ => (desc.apply)(&mut options, value),
+            }
+        }
+
+        (options, errors)
+    }
+
+    /// Directory where the reproducers of the modules are written.
+    ///
+    /// It defaults to a `gccjit-reproducers` directory next to the outputs of the crate (i.e. in
+    /// the target directory when building with Cargo).
+    pub fn reproducer_dir(&self, output_filenames: &OutputFilenames) -> PathBuf {
+        match self.reproducer_dir {
+            Some(ref dir) => dir.clone(),
+            None => output_filenames.out_directory.join("gccjit-reproducers"),
+        }
+    }
+
+    /// Directory where the pseudo-C code of the modules is written.
+    pub fn dump_dir(&self) -> PathBuf {
+        match self.dump_dir {
+            Some(ref dir) => dir.clone(),
+            None => PathBuf::from("/tmp/gccjit_dumps"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::{GccOptions, ModuleFilter, OptionError};
+
+    fn parse(args: &[&str], env_vars: &[(&str, &str)]) -> (GccOptions, Vec<OptionError>) {
+        let args: Vec<_> = args.iter().map(|arg| arg.to_string()).collect();
+        GccOptions::parse_from(&args, |name| {
+            env_vars.iter()
+                .find(|&&(var, _)| var == name)
+                .map(|&(_, value)| value.to_string())
+        })
+    }
+
+    #[test]
+    fn flags() {
+        let (options, errors) = parse(&["-gcc-instrument-functions"], &[("CG_GCCJIT_DUMP_MODULE_NAMES", "1")]);
+        assert!(errors.is_empty());
+        assert!(options.instrument_functions);
+        assert!(options.dump_module_names);
+        assert!(!options.emulate_128bit_integers);
+
+        let (options, _) = parse(&[], &[("CG_GCCJIT_EMULATE_128BIT_INTEGERS", "0")]);
+        assert!(!options.emulate_128bit_integers);
+    }
+
+    #[test]
+    fn values() {
+        let (options, errors) = parse(
+            &["-gcc-option=-fno-inline", "-gcc-driver-option=-lm", "-fno-tree-vectorize", "-gcc-reproducer-dir=/tmp/arg"],
+            &[("CG_GCCJIT_REPRODUCER_DIR", "/tmp/env"), ("CG_GCCJIT_INSTRUMENT_FUNCTIONS_EXCLUDE", "core::,alloc::")],
+        );
+        assert!(errors.is_empty());
+        assert_eq!(options.command_line_options, ["-fno-inline", "-fno-tree-vectorize"]);
+        assert_eq!(options.driver_options, ["-lm"]);
+        // NOTE: the command-line arguments are applied after the environment variables.
+        assert_eq!(options.reproducer_dir.as_deref(), Some(Path::new("/tmp/arg")));
+        assert_eq!(options.instrument_functions_exclude, ["core::", "alloc::"]);
+    }
+
+    #[test]
+    fn module_lists() {
+        let (options, errors) = parse(&["-gcc-dump-rtl=a,b", "-gcc-dump-rtl=c", "-gcc-dump-gimple"], &[]);
+        assert!(errors.is_empty());
+        assert!(matches!(options.dump_rtl, ModuleFilter::Modules(ref modules) if modules == &["a", "b", "c"]));
+        assert!(options.dump_rtl.matches("b"));
+        assert!(!options.dump_rtl.matches("d"));
+        assert!(matches!(options.dump_gimple, ModuleFilter::All));
+        assert!(matches!(options.dump_code, ModuleFilter::Nothing));
+    }
+
+    #[test]
+    fn aliased_env_vars() {
+        let (options, _) = parse(&[], &[("CG_GCCJIT_DUMP_MODULE", "a,b")]);
+        assert!(matches!(options.dump_reproducer, ModuleFilter::Modules(ref modules) if modules == &["a", "b"]));
+
+        let (options, _) = parse(&[], &[("CG_GCCJIT_DUMP_ALL_MODULES", "1"), ("CG_GCCJIT_DUMP_MODULE", "a")]);
+        assert!(matches!(options.dump_reproducer, ModuleFilter::All));
+    }
+
+    #[test]
+    fn unknown_options() {
+        let (_, errors) = parse(&["-gcc-unknown", "-gcc-dump-modules=a"], &[]);
+        assert_eq!(errors, [OptionError::Unknown("-gcc-unknown".to_string()), OptionError::Unknown("-gcc-dump-modules=a".to_string())]);
+    }
+
+    #[test]
+    fn missing_values() {
+        let (options, errors) = parse(&["-gcc-option", "-gcc-reproducer-dir="], &[("CG_GCCJIT_DUMP_DIR", "")]);
+        assert_eq!(errors, [OptionError::MissingValue("-gcc-option".to_string()), OptionError::MissingValue("-gcc-reproducer-dir=".to_string())]);
+        assert!(options.command_line_options.is_empty());
+        assert!(options.reproducer_dir.is_none());
+        assert!(options.dump_dir.is_none());
+    }
+
+    #[test]
+    fn unexpected_values() {
+        let (options, errors) = parse(&["-gcc-instrument-functions=yes"], &[]);
+        assert_eq!(errors, [OptionError::UnexpectedValue("-gcc-instrument-functions=yes".to_string())]);
+        assert!(!options.instrument_functions);
+    }
+}
@@ -174,7 +174,8 @@ impl<'gcc, 'tcx> BaseTypeMethods<'tcx> for CodegenCx<'gcc, 'tcx> {
     }
 
     fn vector_length(&self, _ty: Type<'gcc>) -> usize {
-        unimplemented!();
+        self.report_unsupported("`vector_length`");
+        0
     }
 
     fn float_width(&self, typ: Type<'gcc>) -> usize {
@@ -102,6 +102,11 @@ while [[ $# -gt 0 ]]; do
             shift
             ;;
 
+        "--lang-tests")
+            funcs+=(lang_tests)
+            shift
+            ;;
+
         "--build-sysroot")
             funcs+=(build_sysroot)
             shift
@@ -236,6 +241,12 @@ EOF
     git checkout $(rustc -V | cut -d' ' -f3 | tr -d '(') tests
 }
 
+function lang_tests() {
+    echo "[TEST] lang tests"
+    # The RUSTFLAGS from config.sh are only meant for the test programs, not for the test runner.
+    TEST_FLAGS="$TEST_FLAGS --target $TARGET_TRIPLE $linker" RUN_WRAPPER="$RUN_WRAPPER" RUSTFLAGS= cargo test $flags
+}
+
 function asm_tests() {
     setup_rustc
 
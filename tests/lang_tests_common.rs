@@ -8,6 +8,9 @@ use std::{
 use lang_tester::LangTester;
 use tempfile::TempDir;
 
+/// The tests requiring features that are only available with the master branch of libgccjit.
+const MASTER_ONLY_TESTS: &[&str] = &["asm_unwind.rs", "panic_cannot_unwind_without_handler.rs"];
+
 /// Controls the compile options (e.g., optimization level) used to compile
 /// test code.
 #[allow(dead_code)] // Each test crate picks one variant
@@ -25,7 +28,11 @@ pub fn main_inner(profile: Profile) {
     env::set_var("LD_LIBRARY_PATH", gcc_path);
     LangTester::new()
         .test_dir("tests/run")
-        .test_file_filter(|path| path.extension().expect("extension").to_str().expect("to_str") == "rs")
+        .test_file_filter(|path| {
+            let file_name = path.file_name().expect("file_name").to_str().expect("to_str");
+            path.extension().expect("extension").to_str().expect("to_str") == "rs"
+                && (cfg!(feature="master") || !MASTER_ONLY_TESTS.contains(&file_name))
+        })
         .test_extract(|source| {
             let lines =
                 source.lines()
@@ -45,7 +52,6 @@ pub fn main_inner(profile: Profile) {
             compiler.args(&[
                 &format!("-Zcodegen-backend={}/target/debug/librustc_codegen_gcc.so", current_dir),
                 "--sysroot", &format!("{}/build_sysroot/sysroot/", current_dir),
-                "-Zno-parallel-llvm",
                 "-C", "link-arg=-lc",
                 "-o", exe.to_str().expect("to_str"),
                 path.to_str().expect("to_str"),
@@ -65,7 +71,16 @@ pub fn main_inner(profile: Profile) {
                 }
             }
             // Test command 2: run `tempdir/x`.
-            let runtime = Command::new(exe);
+            let runtime =
+                match option_env!("RUN_WRAPPER").map(|wrapper| wrapper.split_whitespace().collect::<Vec<_>>()) {
+                    Some(wrapper) if !wrapper.is_empty() => {
+                        let mut runtime = Command::new(wrapper[0]);
+                        runtime.args(&wrapper[1..]);
+                        runtime.arg(exe);
+                        runtime
+                    },
+                    _ => Command::new(exe),
+                };
             vec![("Compiler", compiler), ("Run-time", runtime)]
         })
         .run();
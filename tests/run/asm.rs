@@ -164,6 +164,13 @@ fn main() {
     }
     assert_eq!(x, 42);
 
+    // check that the stack is aligned when `nostack` is not specified
+    let sp: u64;
+    unsafe {
+        asm!("mov {}, rsp", out(reg) sp);
+    }
+    assert_eq!(sp % 16, 0);
+
     assert_eq!(unsafe { add_asm(40, 2) }, 42);
 
     let array1 = [1u8, 2, 3];
@@ -0,0 +1,51 @@
+// Compiler:
+//
+// Run-time:
+//   status: 0
+
+use std::arch::asm;
+
+static mut STACK_POINTER: usize = 1;
+
+// Without `options(nostack)`, the stack must be aligned to 16 bytes inside the asm block, whatever
+// the alignment of the stack pointer was before.
+extern "C" fn record_stack_pointer() {
+    let stack_pointer: usize;
+    unsafe {
+        #[cfg(target_arch = "x86_64")]
+        asm!("mov {}, rsp", out(reg) stack_pointer);
+        #[cfg(target_arch = "x86")]
+        asm!("mov {}, esp", out(reg) stack_pointer);
+        STACK_POINTER = stack_pointer;
+    }
+}
+
+macro_rules! check_with_offset {
+    ($offset:literal) => {
+        unsafe {
+            STACK_POINTER = 1;
+            #[cfg(target_arch = "x86_64")]
+            asm!(
+                concat!("sub rsp, ", $offset, "\n\tcall {}\n\tadd rsp, ", $offset),
+                sym record_stack_pointer,
+                clobber_abi("C"),
+            );
+            #[cfg(target_arch = "x86")]
+            asm!(
+                concat!("sub esp, ", $offset, "\n\tcall {}\n\tadd esp, ", $offset),
+                sym record_stack_pointer,
+                clobber_abi("C"),
+            );
+            assert_eq!(STACK_POINTER % 16, 0);
+        }
+    };
+}
+
+fn main() {
+    check_with_offset!(0);
+    #[cfg(target_arch = "x86")]
+    check_with_offset!(4);
+    check_with_offset!(8);
+    #[cfg(target_arch = "x86")]
+    check_with_offset!(12);
+}
@@ -0,0 +1,60 @@
+// Compiler:
+//
+// Run-time:
+//   status: 0
+
+#![feature(asm_unwind)]
+
+use std::arch::asm;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+static DROPPED: AtomicUsize = AtomicUsize::new(0);
+
+struct Guard;
+
+impl Drop for Guard {
+    fn drop(&mut self) {
+        DROPPED.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+extern "C-unwind" fn panicking() {
+    panic!("panic from inline asm");
+}
+
+extern "C-unwind" fn panicking_with_value() -> u32 {
+    panic!("panic from pure inline asm");
+}
+
+// NOTE: this asm block realigns the stack since it doesn't use `options(nostack)`, so this checks
+// that the unwinder can go through it, including with optimizations (which omit the frame pointer)
+// in lang_tests_release.
+#[inline(never)]
+fn call_panicking() {
+    let _guard = Guard;
+    unsafe {
+        asm!("call {}", sym panicking, clobber_abi("C"), options(may_unwind));
+    }
+}
+
+// NOTE: a pure asm block is not volatile, but it must still be able to unwind.
+#[inline(never)]
+fn call_panicking_pure() -> u32 {
+    let _guard = Guard;
+    let value: u32;
+    unsafe {
+        asm!("call {}", sym panicking_with_value, out("eax") value, clobber_abi("C"), options(pure, nomem, may_unwind));
+    }
+    value
+}
+
+fn main() {
+    std::panic::set_hook(Box::new(|_| {}));
+    let result = std::panic::catch_unwind(call_panicking);
+    assert!(result.is_err());
+    assert_eq!(DROPPED.load(Ordering::SeqCst), 1);
+
+    let result = std::panic::catch_unwind(|| std::hint::black_box(call_panicking_pure()));
+    assert!(result.is_err());
+    assert_eq!(DROPPED.load(Ordering::SeqCst), 2);
+}
@@ -0,0 +1,44 @@
+// Compiler:
+//   extra-args: -Zcf-protection=full
+//
+// Run-time:
+//   status: 0
+
+// NOTE: the executable isn't marked as compatible with CET in its .note.gnu.property section since
+// the standard library is not compiled with -Zcf-protection, so this checks that the functions of
+// this crate, including the C main wrapper, and of the allocator shim start with endbr64.
+
+use std::process::Command;
+
+#[no_mangle]
+#[inline(never)]
+pub extern "C" fn indirect_target(value: u32) -> u32 {
+    value + 1
+}
+
+fn first_instruction(function: &str) -> String {
+    let exe = std::env::current_exe().expect("current exe");
+    let output = Command::new("objdump")
+        .arg(format!("--disassemble={}", function))
+        .arg(&exe)
+        .output()
+        .expect("objdump");
+    let disassembly = String::from_utf8_lossy(&output.stdout);
+    disassembly.lines()
+        .skip_while(|line| !line.ends_with(&format!("<{}>:", function)))
+        .nth(1)
+        .unwrap_or_else(|| panic!("no disassembly for {}: {}", function, disassembly))
+        .to_string()
+}
+
+fn main() {
+    let function = std::hint::black_box(indirect_target as extern "C" fn(u32) -> u32);
+    assert_eq!(function(41), 42);
+    let boxed = Box::new(function);
+    std::hint::black_box(&boxed);
+
+    for function in ["indirect_target", "main", "__rust_alloc"] {
+        let instruction = first_instruction(function);
+        assert!(instruction.contains("endbr64"), "{}: {}", function, instruction);
+    }
+}
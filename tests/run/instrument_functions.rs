@@ -0,0 +1,50 @@
+// Compiler:
+//   extra-args: -Cllvm-args=-gcc-instrument-functions
+//     -Cllvm-args=-gcc-instrument-functions-exclude=instrument_functions::hooks
+//
+// Run-time:
+//   status: 0
+
+// NOTE: the C main wrapper must not be instrumented, but the functions that have `main` in their
+// symbol must still be.
+
+mod hooks {
+    pub static mut ENTERED: [usize; 4096] = [0; 4096];
+    pub static mut ENTERED_COUNT: usize = 0;
+
+    #[no_mangle]
+    pub extern "C" fn __cyg_profile_func_enter(this_fn: usize, _call_site: usize) {
+        unsafe {
+            if ENTERED_COUNT < ENTERED.len() {
+                ENTERED[ENTERED_COUNT] = this_fn;
+                ENTERED_COUNT += 1;
+            }
+        }
+    }
+
+    #[no_mangle]
+    pub extern "C" fn __cyg_profile_func_exit(_this_fn: usize, _call_site: usize) {
+    }
+}
+
+extern "C" {
+    #[link_name = "main"]
+    fn c_main(argc: i32, argv: *const *const u8) -> i32;
+}
+
+#[inline(never)]
+fn remaining_main_work(value: u32) -> u32 {
+    value * 2
+}
+
+fn entered(function: usize) -> bool {
+    unsafe {
+        hooks::ENTERED[..hooks::ENTERED_COUNT].contains(&function)
+    }
+}
+
+fn main() {
+    assert_eq!(remaining_main_work(std::hint::black_box(21)), 42);
+    assert!(entered(remaining_main_work as usize));
+    assert!(!entered(c_main as usize));
+}
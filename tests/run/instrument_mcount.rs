@@ -0,0 +1,29 @@
+// Compiler:
+//   extra-args: -Zinstrument-mcount
+//
+// Run-time:
+//   status: 0
+
+// NOTE: on x86, the functions call __fentry__ at their entry, before the prologue.
+
+use std::process::Command;
+
+#[no_mangle]
+#[inline(never)]
+pub extern "C" fn instrumented(value: u32) -> u32 {
+    value + 1
+}
+
+fn main() {
+    assert_eq!(std::hint::black_box(instrumented as extern "C" fn(u32) -> u32)(41), 42);
+
+    let exe = std::env::current_exe().expect("current exe");
+    let output = Command::new("objdump")
+        .arg("--disassemble=instrumented")
+        .arg(&exe)
+        .output()
+        .expect("objdump");
+    let disassembly = String::from_utf8_lossy(&output.stdout);
+    assert!(disassembly.contains("<__fentry__"), "{}", disassembly);
+    assert!(!disassembly.contains("mcount"), "{}", disassembly);
+}
@@ -0,0 +1,132 @@
+// Compiler:
+//
+// Run-time:
+//   status: 0
+
+/*
+ * Code
+ */
+
+use std::hint::black_box;
+
+fn classify(value: u128) -> u8 {
+    match value {
+        0 => 0,
+        1 => 1,
+        0x1_0000_0000_0000_0000 => 2,
+        u128::MAX => 3,
+        _ => 4,
+    }
+}
+
+fn main() {
+    // Comparisons.
+    let a = black_box(0x1_0000_0000_0000_0000_u128);
+    let b = black_box(0xFFFF_FFFF_FFFF_FFFF_u128);
+    assert!(a > b);
+    assert!(a >= b);
+    assert!(b < a);
+    assert!(b <= a);
+    assert!(a != b);
+    assert!(a == a);
+
+    let c = black_box(-1_i128);
+    let d = black_box(1_i128);
+    assert!(c < d);
+    assert!(d > c);
+    assert!(black_box(i128::MIN) < black_box(i128::MAX));
+    assert!((c as u128) > (d as u128));
+
+    // Negation.
+    assert_eq!(-black_box(1_i128), -1);
+    assert_eq!(-black_box(0x1_0000_0000_0000_0000_i128), -0x1_0000_0000_0000_0000_i128);
+    assert_eq!(black_box(i128::MIN).wrapping_neg(), i128::MIN);
+
+    // Bit manipulations.
+    let value = black_box(0x0102_0304_0506_0708_090A_0B0C_0D0E_0F10_u128);
+    assert_eq!(value.swap_bytes(), 0x100F_0E0D_0C0B_0A09_0807_0605_0403_0201);
+    assert_eq!(value.leading_zeros(), 7);
+    assert_eq!(value.trailing_zeros(), 4);
+    assert_eq!(value.count_ones(), 33);
+    assert_eq!(black_box(1_u128 << 100).leading_zeros(), 27);
+    assert_eq!(black_box(1_u128 << 100).trailing_zeros(), 100);
+    assert_eq!(black_box(0_u128).leading_zeros(), 128);
+
+    // Memory layout.
+    assert_eq!(value.to_le_bytes()[0], 0x10);
+    assert_eq!(value.to_be_bytes()[0], 0x01);
+    assert_eq!(u128::from_ne_bytes(value.to_ne_bytes()), value);
+    let halves: [u64; 2] = unsafe { std::mem::transmute(value) };
+    if cfg!(target_endian = "little") {
+        assert_eq!(halves, [0x090A_0B0C_0D0E_0F10, 0x0102_0304_0506_0708]);
+    }
+    else {
+        assert_eq!(halves, [0x0102_0304_0506_0708, 0x090A_0B0C_0D0E_0F10]);
+    }
+    let value: u128 = unsafe { std::mem::transmute(black_box(halves)) };
+    assert_eq!(value >> 64, 0x0102_0304_0506_0708);
+
+    // Float casts.
+    assert_eq!(black_box(1_u128 << 80) as f64, 1208925819614629174706176.0);
+    assert_eq!(black_box(-(1_i128 << 80)) as f64, -1208925819614629174706176.0);
+    assert_eq!(black_box(1208925819614629174706176.0_f64) as u128, 1 << 80);
+    assert_eq!(black_box(-1208925819614629174706176.0_f64) as i128, -(1 << 80));
+    assert_eq!(black_box(12.5_f32) as u128, 12);
+
+    // Arithmetic around the boundary between the halves.
+    let max_low = black_box(u64::MAX as u128);
+    assert_eq!(max_low + 1, 1 << 64);
+    assert_eq!(black_box(1_u128 << 64) - 1, max_low);
+    assert_eq!(max_low * max_low, 0xFFFF_FFFF_FFFF_FFFE_0000_0000_0000_0001);
+    assert_eq!(black_box(u128::MAX) / max_low, 0x1_0000_0000_0000_0001);
+    assert_eq!(black_box(u128::MAX) % black_box(1_u128 << 64), max_low);
+    assert_eq!(black_box(-(1_i128 << 64)) / black_box(3), -6148914691236517205);
+    assert_eq!(black_box(-7_i128) % black_box(1 << 64), -7);
+    assert_eq!(black_box(i64::MIN as i128) - 1, -(1 << 63) - 1);
+
+    // Shifts.
+    assert_eq!(black_box(1_u128) << black_box(64), 1 << 64);
+    assert_eq!(black_box(1_u128 << 63) << black_box(1), 1 << 64);
+    assert_eq!(black_box(1_u128 << 64) >> black_box(1), 1 << 63);
+    assert_eq!(black_box(u128::MAX) >> black_box(127), 1);
+    assert_eq!(black_box(i128::MIN) >> black_box(64), -(1 << 63));
+    assert_eq!(black_box(i128::MIN) >> black_box(127), -1);
+    assert_eq!(black_box(1_u128).wrapping_shl(black_box(129)), 2);
+    assert_eq!(black_box(0x8000_0000_0000_0001_0000_0000_0000_0001_u128).rotate_left(64), 0x1_8000_0000_0000_0001);
+
+    // Overflow-checked operations.
+    assert_eq!(max_low.checked_add(black_box(1)), Some(1 << 64));
+    assert_eq!(black_box(u128::MAX).checked_add(black_box(1)), None);
+    assert_eq!(black_box(i64::MAX as i128).checked_add(black_box(1)), Some(1 << 63));
+    assert_eq!(black_box(i128::MAX).checked_add(black_box(1)), None);
+    assert_eq!(black_box(i128::MIN).checked_add(black_box(-1)), None);
+    assert_eq!(black_box(1_u128 << 64).overflowing_sub(black_box(1)), (u64::MAX as u128, false));
+    assert_eq!(black_box(0_u128).overflowing_sub(black_box(1)), (u128::MAX, true));
+    assert_eq!(black_box(-(1_i128 << 64)).overflowing_sub(black_box(1)), (-(1 << 64) - 1, false));
+    assert_eq!(black_box(i128::MIN).overflowing_sub(black_box(1)), (i128::MAX, true));
+    assert_eq!(black_box(i128::MAX).overflowing_sub(black_box(-1)), (i128::MIN, true));
+    assert_eq!(max_low.checked_mul(max_low), Some(0xFFFF_FFFF_FFFF_FFFE_0000_0000_0000_0001));
+    assert_eq!(black_box(1_u128 << 64).checked_mul(black_box(1 << 64)), None);
+    assert_eq!(black_box(1_u128 << 127).checked_mul(black_box(2)), None);
+    assert_eq!(black_box(0_u128).checked_mul(black_box(u128::MAX)), Some(0));
+    assert_eq!(black_box(1_i128 << 63).checked_mul(black_box(1 << 63)), Some(1 << 126));
+    assert_eq!(black_box(1_i128 << 63).checked_mul(black_box(1 << 64)), None);
+    assert_eq!(black_box(-(1_i128 << 63)).checked_mul(black_box(1 << 64)), Some(i128::MIN));
+    assert_eq!(black_box(i128::MIN).checked_mul(black_box(-1)), None);
+    assert_eq!(black_box(i128::MIN).checked_div(black_box(-1)), None);
+    assert_eq!(black_box(u128::MAX).checked_div(black_box(0)), None);
+
+    // Wrapping operations.
+    assert_eq!(black_box(u128::MAX).wrapping_add(black_box(2)), 1);
+    assert_eq!(black_box(0_u128).wrapping_sub(black_box(1 << 64)), u128::MAX << 64);
+    assert_eq!(black_box(1_u128 << 64).wrapping_mul(black_box(1 << 64)), 0);
+    assert_eq!(black_box(i128::MAX).wrapping_add(black_box(1)), i128::MIN);
+    assert_eq!(black_box(i128::MIN).wrapping_mul(black_box(-1)), i128::MIN);
+
+    // Switches.
+    assert_eq!(classify(black_box(0)), 0);
+    assert_eq!(classify(black_box(1)), 1);
+    assert_eq!(classify(black_box(0x1_0000_0000_0000_0000)), 2);
+    assert_eq!(classify(black_box(u128::MAX)), 3);
+    assert_eq!(classify(black_box(42)), 4);
+}
@@ -0,0 +1,9 @@
+// Compiler:
+//   extra-args: -Cllvm-args=-gcc-emulate-128bit-integers
+//
+// Run-time:
+//   status: 0
+
+// Run the tests of the 128-bit integers with the emulation used on the targets that don't support
+// them natively.
+include!("int128.rs");
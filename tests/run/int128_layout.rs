@@ -0,0 +1,69 @@
+// Compiler:
+//   extra-args: -Cllvm-args=-gcc-emulate-128bit-integers
+//
+// Run-time:
+//   status: 0
+
+// Checks that the halves of the emulated 128-bit integers are stored in the order of the target
+// endianness. The big-endian layout is tested by running the lang tests for a big-endian target,
+// e.g. with the m68k setup (`./test.sh --lang-tests`).
+
+use std::hint::black_box;
+
+static VALUE: u128 = 0x0102_0304_0506_0708_090A_0B0C_0D0E_0F10;
+static NEGATIVE: i128 = -2;
+
+const BIG_ENDIAN_BYTES: [u8; 16] = [1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16];
+
+struct Wrapper {
+    tag: u8,
+    value: u128,
+}
+
+/// Returns the bytes of the integer as they're stored in memory.
+fn memory_bytes<T>(value: &T) -> [u8; 16] {
+    assert_eq!(std::mem::size_of::<T>(), 16);
+    unsafe { std::ptr::read_unaligned(value as *const T as *const [u8; 16]) }
+}
+
+/// Returns the bytes as they're stored in memory for the target endianness.
+fn native_bytes(mut big_endian_bytes: [u8; 16]) -> [u8; 16] {
+    if cfg!(target_endian = "little") {
+        big_endian_bytes.reverse();
+    }
+    big_endian_bytes
+}
+
+fn main() {
+    // Constants.
+    assert_eq!(memory_bytes(&VALUE), native_bytes(BIG_ENDIAN_BYTES));
+    assert_eq!(memory_bytes(&NEGATIVE), native_bytes([0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFE]));
+
+    // Values built from their halves.
+    let value = black_box(0x0102_0304_0506_0708_u128) << 64 | black_box(0x090A_0B0C_0D0E_0F10_u128);
+    assert_eq!(memory_bytes(&value), native_bytes(BIG_ENDIAN_BYTES));
+    assert_eq!(value, VALUE);
+
+    // Casts.
+    let extended = black_box(0x090A_0B0C_0D0E_0F10_u64) as u128;
+    assert_eq!(memory_bytes(&extended), native_bytes([0, 0, 0, 0, 0, 0, 0, 0, 9, 10, 11, 12, 13, 14, 15, 16]));
+    let sign_extended = black_box(-2_i64) as i128;
+    assert_eq!(memory_bytes(&sign_extended), memory_bytes(&NEGATIVE));
+    assert_eq!(black_box(VALUE) as u64, 0x090A_0B0C_0D0E_0F10);
+    assert_eq!((black_box(VALUE) >> 64) as u64, 0x0102_0304_0506_0708);
+
+    // Values read from memory.
+    let bytes = native_bytes(BIG_ENDIAN_BYTES);
+    let read = unsafe { std::ptr::read_unaligned(bytes.as_ptr() as *const u128) };
+    assert_eq!(read, VALUE);
+    assert_eq!(read >> 120, 1);
+    assert_eq!(read & 0xFF, 16);
+    assert_eq!(u128::from_be_bytes(BIG_ENDIAN_BYTES), VALUE);
+    assert_eq!(VALUE.to_ne_bytes(), native_bytes(BIG_ENDIAN_BYTES));
+
+    // Fields.
+    let wrapper = black_box(Wrapper { tag: 1, value: VALUE });
+    assert_eq!(wrapper.tag, 1);
+    assert_eq!(memory_bytes(&wrapper.value), native_bytes(BIG_ENDIAN_BYTES));
+    assert_eq!(wrapper.value >> 64, 0x0102_0304_0506_0708);
+}
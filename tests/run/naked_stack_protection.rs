@@ -0,0 +1,30 @@
+// Compiler:
+//   extra-args: -Zstack-protector=all
+//   status: error
+//   stderr: ...
+//     ...the stack protector cannot be disabled in a single function by the GCC backend...
+//     ...
+//     ...help: move the naked functions into a module of their own...
+//     ...
+//     ...stack probing cannot be disabled in a single function by the GCC backend...
+//     ...
+
+#![feature(naked_functions)]
+
+use std::arch::asm;
+
+// NOTE: neither a canary nor stack probes must be added to the prologue of a naked function, but
+// they can only be disabled in the whole codegen unit, which also contains `main` here.
+#[naked]
+#[no_mangle]
+pub unsafe extern "C" fn naked_return() {
+    asm!("ret", options(noreturn));
+}
+
+fn main() {
+    let mut buffer = [0u8; 64];
+    for (index, byte) in buffer.iter_mut().enumerate() {
+        *byte = index as u8;
+    }
+    assert_eq!(buffer.iter().map(|&byte| byte as u32).sum::<u32>(), 2016);
+}
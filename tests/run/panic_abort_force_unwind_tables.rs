@@ -0,0 +1,58 @@
+// Compiler:
+//   extra-args: -Cpanic=abort -Cforce-unwind-tables=yes -Csave-temps
+//
+// Run-time:
+//   status: 0
+
+// NOTE: -Cforce-unwind-tables emits the unwind tables, e.g. for backtraces, but the objects of the
+// crate must still neither reference the personality function nor have exception tables.
+
+use std::fs;
+use std::process::Command;
+
+struct Guard(u32);
+
+impl Drop for Guard {
+    fn drop(&mut self) {
+        std::hint::black_box(self.0);
+    }
+}
+
+#[inline(never)]
+fn may_panic(value: u32) -> u32 {
+    let _guard = Guard(value);
+    assert!(value < 100);
+    value + 1
+}
+
+fn main() {
+    assert_eq!(may_panic(std::hint::black_box(41)), 42);
+
+    // NOTE: -Csave-temps keeps the objects of the crate next to the executable.
+    let exe = std::env::current_exe().expect("current exe");
+    let prefix = format!("{}.", exe.file_stem().expect("file stem").to_string_lossy());
+    let objects: Vec<_> = fs::read_dir(exe.parent().expect("parent")).expect("read dir")
+        .map(|entry| entry.expect("entry").path())
+        .filter(|path| {
+            let name = path.file_name().expect("file name").to_string_lossy();
+            name.starts_with(&prefix) && name.ends_with(".o")
+        })
+        .collect();
+    assert!(!objects.is_empty());
+
+    for object in &objects {
+        let sections = Command::new("readelf").arg("-S").arg("-W").arg(object).output().expect("readelf");
+        let sections = String::from_utf8_lossy(&sections.stdout);
+        assert!(!sections.contains(".gcc_except_table"), "{}: {}", object.display(), sections);
+
+        let symbols = Command::new("nm").arg(object).output().expect("nm");
+        let symbols = String::from_utf8_lossy(&symbols.stdout);
+        assert!(!symbols.contains("rust_eh_personality"), "{}: {}", object.display(), symbols);
+    }
+
+    let has_unwind_tables = objects.iter().any(|object| {
+        let sections = Command::new("readelf").arg("-S").arg("-W").arg(object).output().expect("readelf");
+        String::from_utf8_lossy(&sections.stdout).contains(".eh_frame")
+    });
+    assert_eq!(has_unwind_tables, true);
+}
@@ -0,0 +1,22 @@
+// Compiler:
+//
+// Run-time:
+//   status: signal
+//   stderr: ...
+//     ...panic in a function that cannot unwind...
+//     ...
+
+// NOTE: a panic escaping an `extern "C"` function must abort, even if a caller would catch it.
+// Since a caller has a handler, the landing pad of the function is reached during the cleanup
+// phase even if it is a cleanup instead of a filter: see panic_cannot_unwind_without_handler.rs
+// for the case that needs a filter.
+
+extern "C" fn panicking() {
+    panic!("panic from an extern \"C\" function");
+}
+
+fn main() {
+    let _ = std::panic::catch_unwind(|| panicking());
+    // NOTE: reaching this means the panic went through the function.
+    std::process::exit(0);
+}
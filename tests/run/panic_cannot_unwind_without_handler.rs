@@ -0,0 +1,33 @@
+// Compiler:
+//
+// Run-time:
+//   status: signal
+//   stderr: ...
+//     ...panic in a function that cannot unwind...
+//     ...
+
+// NOTE: the thread is started by libc, so there's no handler above the `extern "C"` function. A
+// landing pad that is only a cleanup would make the search phase of the unwinder fail ("failed to
+// initiate panic"), while the filter stops it in the function, which then aborts.
+
+use std::ffi::c_void;
+use std::ptr;
+
+extern "C" {
+    fn pthread_create(thread: *mut usize, attr: *const c_void, start_routine: extern "C" fn(*mut c_void) -> *mut c_void, arg: *mut c_void) -> i32;
+    fn pthread_join(thread: usize, retval: *mut *mut c_void) -> i32;
+}
+
+extern "C" fn panicking(_arg: *mut c_void) -> *mut c_void {
+    panic!("panic from an extern \"C\" function");
+}
+
+fn main() {
+    unsafe {
+        let mut thread = 0;
+        assert_eq!(pthread_create(&mut thread, ptr::null(), panicking, ptr::null_mut()), 0);
+        pthread_join(thread, ptr::null_mut());
+    }
+    // NOTE: reaching this means the panic went through the function.
+    std::process::exit(0);
+}
@@ -0,0 +1,41 @@
+// Compiler:
+//
+// Run-time:
+//   status: 0
+
+// Checks that the landing pads select the right action when a panic crosses an FFI boundary:
+// the panics caught before reaching an `extern "C"` function must not abort.
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+static DROPS: AtomicUsize = AtomicUsize::new(0);
+
+struct Guard;
+
+impl Drop for Guard {
+    fn drop(&mut self) {
+        DROPS.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+extern "C-unwind" fn panicking() {
+    let _guard = Guard;
+    panic!("panic from an extern \"C-unwind\" function");
+}
+
+// This function cannot unwind, so it has a landing pad that aborts, but the panic is caught before
+// reaching it.
+extern "C" fn catching() -> bool {
+    let _guard = Guard;
+    std::panic::catch_unwind(|| panicking()).is_err()
+}
+
+fn main() {
+    std::panic::set_hook(Box::new(|_| {}));
+
+    assert!(std::panic::catch_unwind(|| panicking()).is_err());
+    assert_eq!(DROPS.load(Ordering::SeqCst), 1);
+
+    assert!(catching());
+    assert_eq!(DROPS.load(Ordering::SeqCst), 3);
+}
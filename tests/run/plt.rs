@@ -0,0 +1,42 @@
+// Compiler:
+//   extra-args: -Zplt=no -Csave-temps
+//
+// Run-time:
+//   status: 0
+
+// NOTE: with -Zplt=no, the functions of shared libraries are called through the GOT instead of the
+// PLT.
+
+use std::fs;
+use std::process::Command;
+
+extern "C" {
+    fn abs(value: i32) -> i32;
+}
+
+fn main() {
+    assert_eq!(unsafe { abs(std::hint::black_box(-42)) }, 42);
+
+    // NOTE: -Csave-temps keeps the objects of the crate next to the executable.
+    let exe = std::env::current_exe().expect("current exe");
+    let prefix = format!("{}.", exe.file_stem().expect("file stem").to_string_lossy());
+    let relocations: String = fs::read_dir(exe.parent().expect("parent")).expect("read dir")
+        .map(|entry| entry.expect("entry").path())
+        .filter(|path| {
+            let name = path.file_name().expect("file name").to_string_lossy();
+            name.starts_with(&prefix) && name.ends_with(".o")
+        })
+        .map(|object| {
+            let relocations = Command::new("readelf").arg("-r").arg("-W").arg(&object).output().expect("readelf");
+            String::from_utf8_lossy(&relocations.stdout).into_owned()
+        })
+        .collect();
+
+    let abs_relocations: Vec<_> = relocations.lines()
+        .filter(|line| line.split_whitespace().nth(4) == Some("abs"))
+        .collect();
+    assert!(!abs_relocations.is_empty(), "{}", relocations);
+    for relocation in abs_relocations {
+        assert!(relocation.contains("GOTPCREL") && !relocation.contains("PLT32"), "{}", relocation);
+    }
+}
@@ -0,0 +1,41 @@
+// Compiler:
+//   extra-args: -Zprofile -Zno-profiler-runtime
+//
+// Run-time:
+//   status: 0
+//   stdout: 5050
+
+// NOTE: the profiler runtime of LLVM is not built in the sysroot, so -Zno-profiler-runtime is
+// required. The instrumented code calls the functions of libgcov instead.
+// The .gcno files are written next to the object files during the compilation, and the .gcda
+// files are written next to them by libgcov when the instrumented process exits, so the
+// instrumented code is run in a child process.
+
+use std::fs;
+use std::process::Command;
+
+fn count_files(extension: &str) -> usize {
+    let exe = std::env::current_exe().expect("current exe");
+    let prefix = format!("{}.", exe.file_stem().expect("file stem").to_string_lossy());
+    fs::read_dir(exe.parent().expect("parent")).expect("read dir")
+        .map(|entry| entry.expect("entry").file_name().to_string_lossy().into_owned())
+        .filter(|name| name.starts_with(&prefix) && name.ends_with(extension))
+        .count()
+}
+
+fn main() {
+    if std::env::args().nth(1).as_deref() == Some("child") {
+        let sum: u32 = (1..=std::hint::black_box(100)).sum();
+        println!("{}", sum);
+        return;
+    }
+
+    assert!(count_files(".gcno") > 0);
+    assert_eq!(count_files(".gcda"), 0);
+
+    let exe = std::env::current_exe().expect("current exe");
+    let status = Command::new(exe).arg("child").status().expect("child");
+    assert!(status.success());
+
+    assert!(count_files(".gcda") > 0);
+}
@@ -0,0 +1,46 @@
+// Compiler:
+//   extra-args: -Zprofile -Zno-profiler-runtime -Zprofile-emit=/tmp/rustc_codegen_gcc_profile_emit/profile_emit.gcda
+//
+// Run-time:
+//   status: 0
+//   stdout: 5050
+
+// NOTE: with -Zprofile-emit, the .gcda files are written in the directory of the given path,
+// named after it, while the .gcno files stay next to the object files.
+
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+const DATA_DIRECTORY: &str = "/tmp/rustc_codegen_gcc_profile_emit";
+
+fn count_files(directory: &Path, prefix: &str, extension: &str) -> usize {
+    fs::read_dir(directory).into_iter()
+        .flatten()
+        .map(|entry| entry.expect("entry").file_name().to_string_lossy().into_owned())
+        .filter(|name| name.starts_with(prefix) && name.ends_with(extension))
+        .count()
+}
+
+fn main() {
+    if std::env::args().nth(1).as_deref() == Some("child") {
+        let sum: u32 = (1..=std::hint::black_box(100)).sum();
+        println!("{}", sum);
+        return;
+    }
+
+    let exe = std::env::current_exe().expect("current exe");
+    let output_directory = exe.parent().expect("parent");
+    let data_directory = Path::new(DATA_DIRECTORY);
+    // NOTE: remove the files written by the previous runs.
+    let _ = fs::remove_dir_all(data_directory);
+
+    assert!(count_files(output_directory, "profile_emit.", ".gcno") > 0);
+    assert_eq!(count_files(data_directory, "profile_emit.", ".gcno"), 0);
+
+    let status = Command::new(&exe).arg("child").status().expect("child");
+    assert!(status.success());
+
+    assert!(count_files(data_directory, "profile_emit.", ".gcda") > 0);
+    assert_eq!(count_files(output_directory, "profile_emit.", ".gcda"), 0);
+}
@@ -0,0 +1,66 @@
+// Compiler:
+//   extra-args: -Crelocation-model=static -Csave-temps
+//
+// Run-time:
+//   status: 0
+
+// NOTE: with the static relocation model, the objects of the crate must access neither the
+// variables nor the thread-local variables through the GOT or __tls_get_addr.
+
+#![feature(thread_local)]
+
+use std::cell::Cell;
+use std::fs;
+use std::process::Command;
+
+#[thread_local]
+static mut FAST_COUNTER: u32 = 0;
+
+thread_local! {
+    static COUNTER: Cell<u32> = Cell::new(0);
+}
+
+#[no_mangle]
+pub static mut GLOBAL: u32 = 0;
+
+#[inline(never)]
+fn increment() -> u32 {
+    unsafe {
+        FAST_COUNTER += 1;
+        GLOBAL += 1;
+    }
+    COUNTER.with(|counter| {
+        counter.set(counter.get() + 1);
+        counter.get()
+    })
+}
+
+fn main() {
+    assert_eq!(increment(), 1);
+    assert_eq!(increment(), 2);
+    assert_eq!(std::thread::spawn(increment).join().expect("join"), 1);
+    unsafe {
+        assert_eq!(FAST_COUNTER, 2);
+        assert_eq!(GLOBAL, 3);
+    }
+
+    // NOTE: -Csave-temps keeps the objects of the crate next to the executable.
+    let exe = std::env::current_exe().expect("current exe");
+    let prefix = format!("{}.", exe.file_stem().expect("file stem").to_string_lossy());
+    let objects: Vec<_> = fs::read_dir(exe.parent().expect("parent")).expect("read dir")
+        .map(|entry| entry.expect("entry").path())
+        .filter(|path| {
+            let name = path.file_name().expect("file name").to_string_lossy();
+            name.starts_with(&prefix) && name.ends_with(".o")
+        })
+        .collect();
+    assert!(!objects.is_empty());
+
+    for object in &objects {
+        let relocations = Command::new("readelf").arg("-r").arg("-W").arg(object).output().expect("readelf");
+        let relocations = String::from_utf8_lossy(&relocations.stdout);
+        for relocation in ["GOTPCREL", "TLSGD", "TLSLD", "__tls_get_addr"] {
+            assert!(!relocations.contains(relocation), "{}: {}", object.display(), relocations);
+        }
+    }
+}
@@ -0,0 +1,44 @@
+// Compiler:
+//   extra-args: -Ctarget-feature=+retpoline-external-thunk
+//
+// Run-time:
+//   status: 0
+
+// NOTE: with the external thunks, the indirect calls go through the __x86_indirect_thunk_<reg>
+// functions, which are provided by the user (e.g. by the kernel). Here, they simply jump to the
+// target.
+
+use std::arch::global_asm;
+use std::process::Command;
+
+global_asm!(
+    "
+    .irp reg, rax, rbx, rcx, rdx, rsi, rdi, rbp, r8, r9, r10, r11, r12, r13, r14, r15
+    .global __x86_indirect_thunk_\\reg
+__x86_indirect_thunk_\\reg:
+    jmp \\reg
+    .endr"
+);
+
+#[no_mangle]
+#[inline(never)]
+pub extern "C" fn call_indirect(function: extern "C" fn(u32) -> u32, value: u32) -> u32 {
+    function(value)
+}
+
+extern "C" fn increment(value: u32) -> u32 {
+    value + 1
+}
+
+fn main() {
+    assert_eq!(call_indirect(std::hint::black_box(increment), 41), 42);
+
+    let exe = std::env::current_exe().expect("current exe");
+    let output = Command::new("objdump")
+        .arg("--disassemble=call_indirect")
+        .arg(&exe)
+        .output()
+        .expect("objdump");
+    let disassembly = String::from_utf8_lossy(&output.stdout);
+    assert!(disassembly.contains("<__x86_indirect_thunk_"), "{}", disassembly);
+}
@@ -0,0 +1,22 @@
+// Compiler:
+//   extra-args: -Ctarget-feature=-mmx,-sse,+soft-float
+//   status: error
+//   stderr: ...
+//     ...passing floats without the SSE registers is not supported by the GCC backend
+//     ...
+//     ...note: while compiling `fn add`
+//     ...
+
+// NOTE: the C ABI of x86_64 passes the floats in the SSE registers even with -msoft-float, so GCC
+// cannot compile the functions taking or returning floats when they are disabled, like on the
+// kernel targets.
+
+#[inline(never)]
+fn add(a: f32, b: f32) -> f32 {
+    a + b
+}
+
+fn main() {
+    let sum = add(std::hint::black_box(1.5), 2.0);
+    std::process::exit(sum as i32);
+}
@@ -0,0 +1,25 @@
+// Compiler:
+//   extra-args: -Zstack-protector=all
+//
+// Run-time:
+//   status: signal
+//   stderr: ...
+//     ...stack smashing detected...
+//     ...
+
+#[inline(never)]
+fn smash(length: usize) {
+    let mut buffer = [0u8; 16];
+    let pointer = buffer.as_mut_ptr();
+    for index in 0..length {
+        // NOTE: this overwrites the canary placed above the buffer.
+        unsafe {
+            pointer.add(index).write_volatile(0xAA);
+        }
+    }
+    std::hint::black_box(&buffer);
+}
+
+fn main() {
+    smash(std::hint::black_box(64));
+}
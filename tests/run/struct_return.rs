@@ -0,0 +1,68 @@
+// Compiler:
+//
+// Run-time:
+//   status: 0
+
+// On x86, the structs returned in memory are returned through a hidden pointer popped by the
+// callee, so the calls to C functions returning them must not pop it again.
+
+use std::hint::black_box;
+
+#[repr(C)]
+#[derive(Debug, PartialEq)]
+struct Division {
+    quot: i32,
+    rem: i32,
+}
+
+#[repr(C)]
+#[derive(Debug, PartialEq)]
+struct LongLongDivision {
+    quot: i64,
+    rem: i64,
+}
+
+extern "C" {
+    fn div(numerator: i32, denominator: i32) -> Division;
+    fn lldiv(numerator: i64, denominator: i64) -> LongLongDivision;
+}
+
+#[repr(C)]
+struct Large {
+    values: [u32; 8],
+}
+
+extern "C" fn make_large(value: u32) -> Large {
+    Large {
+        values: [value; 8],
+    }
+}
+
+// NOTE: the calls are made in a loop so that a wrong stack pointer after the calls would make
+// the function return to a wrong address when the frame pointer is omitted.
+#[inline(never)]
+fn sum_divisions(count: i64) -> i64 {
+    let mut sum = 0;
+    for i in 0..count {
+        let division = unsafe { div(black_box(i as i32), 3) };
+        let long_division = unsafe { lldiv(black_box(i), 7) };
+        sum += division.quot as i64 + division.rem as i64 + long_division.quot + long_division.rem;
+    }
+    sum
+}
+
+#[inline(never)]
+fn sum_large(count: u32, make: extern "C" fn(u32) -> Large) -> u32 {
+    let mut sum = 0;
+    for i in 0..count {
+        sum += make(black_box(i)).values.iter().sum::<u32>();
+    }
+    sum
+}
+
+fn main() {
+    assert_eq!(unsafe { div(black_box(17), 5) }, Division { quot: 3, rem: 2 });
+    assert_eq!(unsafe { lldiv(black_box(-(1 << 40) - 3), 1 << 20) }, LongLongDivision { quot: -(1 << 20), rem: -3 });
+    assert_eq!(sum_divisions(black_box(1000)), (0..1000_i64).map(|i| i / 3 + i % 3 + i / 7 + i % 7).sum::<i64>());
+    assert_eq!(sum_large(black_box(100), black_box(make_large)), 8 * 4950);
+}
@@ -0,0 +1,29 @@
+// Compiler:
+//   extra-args: -Ctarget-cpu=haswell
+//
+// Run-time:
+//   status: 0
+
+// NOTE: the CPU selected with -Ctarget-cpu must apply to the code of the whole module: lzcnt is only
+// available from Haswell, while bsr is used with the default CPU of x86-64.
+
+use std::process::Command;
+
+#[no_mangle]
+#[inline(never)]
+pub extern "C" fn leading_zeros(value: u64) -> u32 {
+    value.leading_zeros()
+}
+
+fn main() {
+    std::hint::black_box(leading_zeros as extern "C" fn(u64) -> u32);
+
+    let exe = std::env::current_exe().expect("current exe");
+    let output = Command::new("objdump")
+        .arg("--disassemble=leading_zeros")
+        .arg(&exe)
+        .output()
+        .expect("objdump");
+    let disassembly = String::from_utf8_lossy(&output.stdout);
+    assert!(disassembly.contains("lzcnt"), "{}", disassembly);
+}
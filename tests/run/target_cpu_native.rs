@@ -0,0 +1,17 @@
+// Compiler:
+//   extra-args: -Ctarget-cpu=native
+//     -Ztune-cpu=native
+//
+// Run-time:
+//   status: 0
+//   stdout: 55
+
+// NOTE: native is only handled by the GCC driver, not by libgccjit, so the default CPU must be used
+// instead of crashing.
+
+use std::hint::black_box;
+
+fn main() {
+    let sum: u32 = (1..=black_box(10)).sum();
+    println!("{}", sum);
+}
@@ -0,0 +1,31 @@
+// Compiler:
+//   status: error
+//   stderr: ...
+//     ...the `breakpoint` intrinsic is not supported by the GCC backend
+//     ...
+//     ...note: while compiling `fn first_breakpoint`
+//     ...
+//     ...the `breakpoint` intrinsic is not supported by the GCC backend
+//     ...
+//     ...note: while compiling `fn second_breakpoint`
+//     ...
+
+// NOTE: an unsupported feature is reported as an error pointing to the item using it, and the
+// compilation continues so that the unsupported features of the other items are also reported.
+
+#![feature(core_intrinsics)]
+
+#[inline(never)]
+fn first_breakpoint() {
+    std::intrinsics::breakpoint();
+}
+
+#[inline(never)]
+fn second_breakpoint() {
+    std::intrinsics::breakpoint();
+}
+
+fn main() {
+    first_breakpoint();
+    second_breakpoint();
+}